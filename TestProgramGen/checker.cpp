@@ -208,9 +208,10 @@ std::string st_display(const int st) {
   }
 }
 
-int is_valid_expected_value(const mapping &m) {
-  /* the number of running threads should be the same as the number of the cores
-   * number of cores */
+int is_valid_expected_value(const mapping &m, const exp_val_t &exp) {
+  /* the number of running threads should be the same as in one of the
+   * candidates, which leave cores idle when the other threads are blocked or
+   * may not run on them */
 
   // count the number of running threads
   int running_count = 0;
@@ -220,15 +221,26 @@ int is_valid_expected_value(const mapping &m) {
     }
   }
 
-  int num_threads = m.size();
+  for (const auto &thread_state : exp.thread_states) {
+    if (std::count(thread_state.begin(), thread_state.end(), RUNNING) ==
+        running_count) {
+      return 1;
+    }
+  }
+  return 0;
+}
 
-  if (num_threads < NUM_CORES) {
-    return running_count == num_threads;
-  } else {
-    return running_count == NUM_CORES;
+int match_thread_state(const mapping &tid2realstate,
+                       const std::vector<int> &thread_state) {
+  for (const auto &[tid, realstate] : tid2realstate) {
+    if (thread_state.size() <= tid || realstate != thread_state[tid]) {
+      return 0;
+    }
   }
+  return 1;
 }
 
+// returns the 1-based index of the matched candidate, or 0 if none matches
 int checker(const exp_val_t &exp) {
   mapping tid2realstate = {};
   std::vector<std::vector<std::string>> tid2stat = {};
@@ -251,15 +263,15 @@ int checker(const exp_val_t &exp) {
       tid2stat[tid] = stat;
     }
     tid2realstate = get_states(tid2stat);
-  } while (!is_valid_expected_value(tid2realstate));
+  } while (!is_valid_expected_value(tid2realstate, exp));
 
-  for (const auto &[tid, realstate] : tid2realstate) {
-    if (exp.thread_state.size() < tid || realstate != exp.thread_state[tid]) {
-      return 0;
+  for (int i = 0; i < exp.thread_states.size(); i++) {
+    if (match_thread_state(tid2realstate, exp.thread_states[i])) {
+      return i + 1;
     }
   }
 
-  return 1;
+  return 0;
 }
 
 /*********************************/
@@ -283,17 +295,17 @@ void debug_print_st_mapping(const mapping &m, const exp_val_t &exp) {
       debug_printf("[%d]: TID ?: %s (expected: unknown)\n", i++,
                    st_display(st).c_str());
     } else {
-      int exp_state;
-      try {
-        exp_state = exp.thread_state.at(tid);
-        debug_printf("[%d]: TID %d: %s (expected: %s)\n", i++, tid,
-                     st_display(st).c_str(), st_display(exp_state).c_str());
-      } catch (std::out_of_range &e) {
-        debug_printf("[ERROR] TID %d is not found in the expected state\n",
-                     tid);
-        debug_printf("[%d]: TID %d: %s (expected: ?)\n", i++, tid,
-                     st_display(st).c_str());
+      // the expected states of all candidates, e.g. "Ready/Running"
+      std::string exp_states;
+      for (const auto &thread_state : exp.thread_states) {
+        if (!exp_states.empty()) {
+          exp_states += "/";
+        }
+        exp_states += tid < thread_state.size() ? st_display(thread_state[tid])
+                                                : "?";
       }
+      debug_printf("[%d]: TID %d: %s (expected: %s)\n", i++, tid,
+                   st_display(st).c_str(), exp_states.c_str());
     }
   }
 }
@@ -318,7 +330,7 @@ void debug_print_current_st(const exp_val_t &exp) {
       tid2stat[tid] = stat;
     }
     tid2realstate = get_states(tid2stat);
-  } while (!is_valid_expected_value(tid2realstate));
+  } while (!is_valid_expected_value(tid2realstate, exp));
 
   debug_print_st_mapping(tid2realstate, exp);
 }
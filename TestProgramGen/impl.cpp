@@ -5,20 +5,60 @@ extern void *thread(void *);
 
 volatile unsigned int new_tid = 1;
 
-void impl_pthread_create(const int invoker, const int new_prio) {
+void impl_pthread_cond_broadcast(const int invoker) {
+  debug_printf("%d: PthreadCondBroadcast(%llu)\n", seq_idx, invoker);
+  expected_errno = pthread_cond_broadcast(&cond);
+}
+
+void impl_pthread_cond_signal(const int invoker) {
+  debug_printf("%d: PthreadCondSignal(%llu)\n", seq_idx, invoker);
+  expected_errno = pthread_cond_signal(&cond);
+}
+
+void impl_pthread_cond_wait(const int invoker) {
+  debug_printf("%d: PthreadCondWait(%llu)\n", seq_idx, invoker);
+  expected_errno = pthread_cond_wait(&cond, &mutex);
+}
+
+// bit i of the mask allows CPU i
+static cpu_set_t to_cpu_set(const int mask) {
+  cpu_set_t set;
+  CPU_ZERO(&set);
+  for (int cpu = 0; cpu < 32; cpu++) {
+    if (mask & (1 << cpu)) {
+      CPU_SET(cpu, &set);
+    }
+  }
+  return set;
+}
+
+// new_policy is SCHED_FIFO or SCHED_RR, detach_state is PTHREAD_CREATE_JOINABLE
+// or PTHREAD_CREATE_DETACHED, affinity 0 inherits the CPUs of the invoker
+void impl_pthread_create(const int invoker, const int new_prio,
+                         const int new_policy, const int detach_state,
+                         const int affinity) {
   pthread_t tid;
   pthread_attr_t attr;
   pthread_attr_init(&attr);
-  pthread_attr_setdetachstate(&attr, PTHREAD_CREATE_DETACHED);
+  pthread_attr_setdetachstate(&attr, detach_state);
+  if (affinity != 0) {
+    cpu_set_t set = to_cpu_set(affinity);
+    pthread_attr_setaffinity_np(&attr, sizeof(cpu_set_t), &set);
+  }
   pthread_attr_setinheritsched(&attr, PTHREAD_EXPLICIT_SCHED);
-  pthread_attr_setschedpolicy(&attr, SCHED_FIFO);
+  pthread_attr_setschedpolicy(&attr, new_policy);
   struct sched_param param;
   param.sched_priority = new_prio;
   pthread_attr_setschedparam(&attr, &param);
-  pthread_create(&tid, &attr, thread, (void *)(unsigned long long int)new_tid);
-  tid_mapping.emplace(tid, new_tid);
+  expected_errno =
+      pthread_create(&tid, &attr, thread, (void *)(unsigned long long int)new_tid);
+  if (expected_errno == 0) {
+    tid_mapping.emplace(tid, new_tid);
+    // the new thread may be targeted before it first runs
+    thread_handles[new_tid] = tid;
+    new_tid++;
+  }
   pthread_attr_destroy(&attr);
-  new_tid++;
   /* old impl */
   // const int new_tid{arg[0]};
   // const int new_prio{arg[1]};
@@ -55,6 +95,11 @@ void impl_pthread_exit(const int invoker) {
   pthread_exit(NULL);
 }
 
+void impl_pthread_join(const int invoker, const int target) {
+  debug_printf("%d: PthreadJoin(%llu)\n", seq_idx, invoker);
+  expected_errno = pthread_join(thread_handles[target], NULL);
+}
+
 void impl_pthread_mutex_lock(const int invoker) {
   debug_printf("%d: PthreadMutexLock(%llu)\n", seq_idx, invoker);
   expected_errno = pthread_mutex_lock(&mutex);
@@ -65,6 +110,14 @@ void impl_pthread_mutex_lock(const int invoker) {
   // // dec(&mutex_waiting_counter);
 }
 
+void impl_pthread_mutex_setprioceiling(const int invoker,
+                                      const int prioceiling) {
+  debug_printf("%d: PthreadMutexSetprioceiling(%llu)\n", seq_idx, invoker);
+  int old_ceiling;
+  expected_errno =
+      pthread_mutex_setprioceiling(&mutex, prioceiling, &old_ceiling);
+}
+
 void impl_pthread_mutex_try_lock(const int invoker) {
   debug_printf("%d: PthreadMutexTrylock(%llu)\n", seq_idx, invoker);
   expected_errno = pthread_mutex_trylock(&mutex);
@@ -74,3 +127,65 @@ void impl_pthread_mutex_unlock(const int invoker) {
   debug_printf("%d: PthreadMutexUnlock(%llu)\n", seq_idx, invoker);
   expected_errno = pthread_mutex_unlock(&mutex);
 }
+
+void impl_pthread_setaffinity(const int invoker, const int target,
+                              const int mask) {
+  debug_printf("%d: PthreadSetaffinity(%llu)\n", seq_idx, invoker);
+  cpu_set_t set = to_cpu_set(mask);
+  expected_errno =
+      pthread_setaffinity_np(thread_handles[target], sizeof(cpu_set_t), &set);
+}
+
+void impl_pthread_setschedparam(const int invoker, const int target,
+                                const int policy, const int prio) {
+  debug_printf("%d: PthreadSetschedparam(%llu)\n", seq_idx, invoker);
+  struct sched_param param;
+  param.sched_priority = prio;
+  expected_errno =
+      pthread_setschedparam(thread_handles[target], policy, &param);
+}
+
+void impl_pthread_setschedprio(const int invoker, const int target,
+                               const int prio) {
+  debug_printf("%d: PthreadSetschedprio(%llu)\n", seq_idx, invoker);
+  expected_errno = pthread_setschedprio(thread_handles[target], prio);
+}
+
+// spins for two time slices so that the slice of the invoker expires at least
+// once
+void impl_quantum_expiry(const int invoker) {
+  debug_printf("%d: QuantumExpiry(%llu)\n", seq_idx, invoker);
+  struct timespec quantum, now, end;
+  sched_rr_get_interval(0, &quantum);
+  clock_gettime(CLOCK_MONOTONIC, &end);
+  end.tv_sec += 2 * quantum.tv_sec;
+  end.tv_nsec += 2 * quantum.tv_nsec;
+  end.tv_sec += end.tv_nsec / 1000000000;
+  end.tv_nsec %= 1000000000;
+  do {
+    clock_gettime(CLOCK_MONOTONIC, &now);
+  } while (now.tv_sec < end.tv_sec ||
+           (now.tv_sec == end.tv_sec && now.tv_nsec < end.tv_nsec));
+  expected_errno = 0;
+}
+
+void impl_sched_yield(const int invoker) {
+  debug_printf("%d: SchedYield(%llu)\n", seq_idx, invoker);
+  expected_errno = sched_yield();
+}
+
+// the sem_* functions return -1 and set errno on failure
+void impl_sem_post(const int invoker) {
+  debug_printf("%d: SemPost(%llu)\n", seq_idx, invoker);
+  expected_errno = sem_post(&sem) == 0 ? 0 : errno;
+}
+
+void impl_sem_trywait(const int invoker) {
+  debug_printf("%d: SemTrywait(%llu)\n", seq_idx, invoker);
+  expected_errno = sem_trywait(&sem) == 0 ? 0 : errno;
+}
+
+void impl_sem_wait(const int invoker) {
+  debug_printf("%d: SemWait(%llu)\n", seq_idx, invoker);
+  expected_errno = sem_wait(&sem) == 0 ? 0 : errno;
+}
@@ -1,6 +1,25 @@
 #include "util.h"
-extern void impl_pthread_create(const int invoker, const int new_prio);
+extern void impl_pthread_cond_broadcast(const int invoker);
+extern void impl_pthread_cond_signal(const int invoker);
+extern void impl_pthread_cond_wait(const int invoker);
+extern void impl_pthread_create(const int invoker, const int new_prio,
+                                const int new_policy, const int detach_state,
+                                const int affinity);
 extern void impl_pthread_exit(const int invoker);
+extern void impl_pthread_join(const int invoker, const int target);
 extern void impl_pthread_mutex_lock(const int invoker);
+extern void impl_pthread_mutex_setprioceiling(const int invoker,
+                                             const int prioceiling);
 extern void impl_pthread_mutex_try_lock(const int invoker);
 extern void impl_pthread_mutex_unlock(const int invoker);
+extern void impl_pthread_setaffinity(const int invoker, const int target,
+                                     const int mask);
+extern void impl_pthread_setschedparam(const int invoker, const int target,
+                                       const int policy, const int prio);
+extern void impl_pthread_setschedprio(const int invoker, const int target,
+                                      const int prio);
+extern void impl_quantum_expiry(const int invoker);
+extern void impl_sched_yield(const int invoker);
+extern void impl_sem_post(const int invoker);
+extern void impl_sem_trywait(const int invoker);
+extern void impl_sem_wait(const int invoker);
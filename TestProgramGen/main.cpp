@@ -13,6 +13,9 @@ volatile bool init_end;
 
 ull expected_errno;
 pthread_mutex_t mutex;
+pthread_cond_t cond = PTHREAD_COND_INITIALIZER;
+sem_t sem;
+pthread_t thread_handles[MAX_THREADS];
 
 /******************/
 /*  テスト関数群  */
@@ -39,14 +42,21 @@ void alarm_handler(int signum) {
   _exit(EXIT_FAILURE);
 }
 
+// spins until one of the expected states is observed and records which one
+void wait_for_expected(const unsigned int idx) {
+  int matched;
+  while (!(matched = checker(test_seq[idx].exp)))
+    ;
+  debug_printf("%d: matched expected state %d\n", idx, matched - 1);
+}
+
 void check_finish() {
   if (seq_idx >= test_seq_size) {
     unsigned int idx = seq_idx;
     if (idx != 0 && test_seq[idx - 1].func_name == "PthreadExit") {
       // Since after pthread_exit, the thread is terminated and not chech the
       // expected values, so we need to check the expected values here
-      while (!checker(test_seq[idx - 1].exp))
-        ;
+      wait_for_expected(idx - 1);
     }
     debug_print("The test program has finished successfully.\n");
     _exit(EXIT_SUCCESS);
@@ -68,27 +78,57 @@ void thread_context(const ull tid) {
     if (idx != 0 && test_seq[idx - 1].func_name == "PthreadExit") {
       // Since after pthread_exit, the thread is terminated and not chech the
       // expected values, so we need to check the expected values here
-      while (!checker(test_seq[idx - 1].exp))
-        ;
+      wait_for_expected(idx - 1);
     }
-    if (func_name == "PthreadCreate") {
-      debug_printf("%d: PthreadCreate[%d] (TID: %d)\n", idx, arg[0], invoker);
-      impl_pthread_create(tid, arg[0]);
+    if (func_name == "PthreadCondBroadcast") {
+      impl_pthread_cond_broadcast(tid);
+    } else if (func_name == "PthreadCondSignal") {
+      impl_pthread_cond_signal(tid);
+    } else if (func_name == "PthreadCondWait") {
+      impl_pthread_cond_wait(tid);
+    } else if (func_name == "PthreadCreate") {
+      debug_printf("%d: PthreadCreate[%d, %d, %d, %d] (TID: %d)\n", idx,
+                   arg[0], arg[1], arg[2], arg[3], invoker);
+      impl_pthread_create(tid, arg[0], arg[1], arg[2], arg[3]);
     } else if (func_name == "PthreadExit") {
       debug_printf("%d: PthreadExit[] (TID: %d)\n", seq_idx, invoker);
       // Since after pthread_exit, the thread is terminated, so we need to incr
       // the seq_idx here.
       seq_idx++;
       impl_pthread_exit(tid);
+    } else if (func_name == "PthreadJoin") {
+      impl_pthread_join(tid, arg[0]);
     } else if (func_name == "PthreadMutexLock") {
       impl_pthread_mutex_lock(tid);
+    } else if (func_name == "PthreadMutexSetprioceiling") {
+      impl_pthread_mutex_setprioceiling(tid, arg[1]);
     } else if (func_name == "PthreadMutexTrylock") {
       impl_pthread_mutex_try_lock(tid);
     } else if (func_name == "PthreadMutexUnlock") {
       impl_pthread_mutex_unlock(tid);
+    } else if (func_name == "PthreadSetaffinity") {
+      impl_pthread_setaffinity(tid, arg[0], arg[1]);
+    } else if (func_name == "PthreadSetschedparam") {
+      impl_pthread_setschedparam(tid, arg[0], arg[1], arg[2]);
+    } else if (func_name == "PthreadSetschedprio") {
+      impl_pthread_setschedprio(tid, arg[0], arg[1]);
+    } else if (func_name == "QuantumExpiry") {
+      impl_quantum_expiry(tid);
+    } else if (func_name == "SchedYield") {
+      impl_sched_yield(tid);
+    } else if (func_name == "SemPost") {
+      impl_sem_post(tid);
+    } else if (func_name == "SemTrywait") {
+      impl_sem_trywait(tid);
+    } else if (func_name == "SemWait") {
+      impl_sem_wait(tid);
+    }
+    if (expected_errno != (ull)test_seq[idx].ret) {
+      debug_printf("%d: returned %llu (expected: %d)\n", idx, expected_errno,
+                   test_seq[idx].ret);
+      _exit(EXIT_FAILURE);
     }
-    while (!checker(test_seq[idx].exp))
-      ;
+    wait_for_expected(idx);
     seq_idx++;
     alarm(ALRM_TIME);
   }
@@ -97,6 +137,7 @@ void thread_context(const ull tid) {
 void *thread(void *a_tid) {
   const ull tid = (ull)a_tid;
   tid_mapping.emplace(tid, (int)gettid());
+  thread_handles[tid] = pthread_self();
   init_end = true;
 
   while (1) {
@@ -123,20 +164,25 @@ int main() {
   alarm(ALRM_TIME);
   debug_print("[DEBUG] alarm init done\n");
   struct sched_param param;
-  param.sched_priority = sched_get_priority_min(SCHED_FIFO);
-  if (sched_setscheduler(0, SCHED_FIFO, &param) != 0) {
+  param.sched_priority = main_priority;
+  if (sched_setscheduler(0, main_policy, &param) != 0) {
     perror("SET POLICY FAILED\n");
     exit(EXIT_FAILURE);
   }
-  debug_print("[DEBUG] set policy to SCHED_FIFO\n");
+  debug_print("[DEBUG] set policy of the main thread\n");
 
   pthread_mutexattr_t mtx_attr;
   pthread_mutexattr_init(&mtx_attr);
   // pthread_mutexattr_setrobust(&mtx_attr, PTHREAD_MUTEX_ROBUST);
-  //  pthread_mutexattr_settype(&mtx_attr, PTHREAD_MUTEX_ERRORCHECK);
+  pthread_mutexattr_settype(&mtx_attr, PTHREAD_MUTEX_ERRORCHECK);
+  pthread_mutexattr_setprotocol(&mtx_attr, mutex_protocol);
+  pthread_mutexattr_setprioceiling(&mtx_attr, mutex_prioceiling);
   pthread_mutex_init(&mutex, &mtx_attr);
   debug_print("[DEBUG] mutex init done\n");
+  // the model starts every semaphore at 0
+  sem_init(&sem, 0, 0);
   tid_mapping.emplace(0, (int)gettid());
+  thread_handles[0] = pthread_self();
   while (1) {
     thread_context(0);
     check_finish();
@@ -6,6 +6,8 @@
 /******************/
 #define BUSY_LOOP_COUNT (10000000)
 #define ALRM_TIME (6)
+// the number of threads the harness can track
+#define MAX_THREADS (64)
 /****************/
 
 #ifndef _GNU_SOURCE
@@ -22,6 +24,7 @@
 #include <fstream>
 #include <pthread.h>
 #include <sched.h>
+#include <semaphore.h>
 #include <string>
 #include <unistd.h>
 #include <vector>
@@ -36,7 +39,8 @@ using ull = unsigned long long;
 using ll = long long;
 
 typedef struct {
-  std::vector<int> thread_state;
+  // the expectation holds if any of the candidates is observed
+  std::vector<std::vector<int>> thread_states;
 } exp_val_t;
 
 typedef struct {
@@ -44,12 +48,22 @@ typedef struct {
   std::vector<int> arg;
   int invoker;
   exp_val_t exp;
+  // the expected return value (0 or errno)
+  int ret;
 } test_t;
 
 /************************************/
 /*  テストパラメータ・テストケース  */
 /************************************/
-#define NUM_CORES 2
+// defined by the generated test program
+extern const int num_cores;
+// PTHREAD_PRIO_NONE, PTHREAD_PRIO_INHERIT or PTHREAD_PRIO_PROTECT
+extern const int mutex_protocol;
+// the initial priority ceiling used by PTHREAD_PRIO_PROTECT
+extern const int mutex_prioceiling;
+// the scheduling policy and priority of the main thread
+extern const int main_policy;
+extern const int main_priority;
 
 // mapping from real tid to formalized tid
 extern mapping tid_mapping;
@@ -66,6 +80,11 @@ extern volatile bool init_end;
 
 extern ull expected_errno;
 extern pthread_mutex_t mutex;
+extern pthread_cond_t cond;
+extern sem_t sem;
+
+// pthread_t of each thread indexed by the harness TID
+extern pthread_t thread_handles[];
 
 extern test_t test_seq[];
 extern size_t test_seq_size;
@@ -2,6 +2,8 @@
 
 N = 3
 TIMEOUT=300
+# the options of `posix-sched-tester gen`, which writes the test programs into tp/
+GEN_OPTIONS = '--out tp'
 
 import os
 import shutil
@@ -60,7 +62,10 @@ for _ in range(N):
     # run command and write output to log file using tee command
     for i in range(3):
         try:
-            subprocess.run(f'cargo run 2>&1 | tee logs/log{idx}', shell=True, timeout=TIMEOUT)
+            start = time.time()
+            subprocess.run(f'cargo run -- gen {GEN_OPTIONS} 2>&1 | tee logs/log{idx}', shell=True, timeout=TIMEOUT)
+            with open(f'logs/log{idx}', 'a') as f:
+                f.write(f"time: {time.time() - start:.3f}\n")
             subprocess.run(f'sudo dmesg > logs/dmesg.log{idx}', shell=True)
             break
         except subprocess.TimeoutExpired as e:
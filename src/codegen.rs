@@ -0,0 +1,275 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::observation::{self, Observability, Observation};
+use crate::oracle_tree::Step;
+use crate::spec::{
+    config::ModelConfig,
+    function::{get_function, Function, Param},
+    sched_data::{Policy, TaskState},
+    scheduler,
+    sync::{self, MutexProtocol},
+};
+
+// The harness numbers the main thread 0 while the model numbers it 1
+fn harness_tid(tid: u32) -> u32 {
+    tid - 1
+}
+
+// The constants defined in TestProgramGen/util.h
+fn harness_task_state(state: TaskState) -> &'static str {
+    match state {
+        TaskState::Ready => "READY",
+        TaskState::Running => "RUNNING",
+        TaskState::Waiting => "WAITING",
+        TaskState::Terminated => "TERMINATED",
+        TaskState::New => "UNKNOWN",
+    }
+}
+
+fn harness_thread_state(observation: &Observation) -> Vec<&'static str> {
+    let len = observation
+        .0
+        .last()
+        .map_or(0, |thread| harness_tid(thread.tid) as usize + 1);
+    let mut thread_state = vec![harness_task_state(TaskState::New); len];
+    for thread in observation.0.iter() {
+        thread_state[harness_tid(thread.tid) as usize] = harness_task_state(thread.state);
+    }
+    thread_state
+}
+
+// The state of every thread indexed by the harness TID
+pub fn thread_state(state: &scheduler::State) -> Vec<&'static str> {
+    harness_thread_state(&Observation::of(state, Observability::default()))
+}
+
+// The distinct observations of every state the call may lead to, starting with the one on the path
+pub fn expected_thread_states(step: &Step) -> Vec<Vec<&'static str>> {
+    observation::merge(
+        std::iter::once(&step.state).chain(step.group.iter()),
+        Observability::default(),
+    )
+    .iter()
+    .map(|(observation, _)| harness_thread_state(observation))
+    .collect()
+}
+
+fn gen_test_case(step: &Step) -> String {
+    let params = get_function(step.fn_type).params();
+    let args: Vec<String> = step
+        .args
+        .iter()
+        .zip(params.iter())
+        .map(|(&arg, param)| match param {
+            Param::Tid => harness_tid(arg).to_string(),
+            _ => arg.to_string(),
+        })
+        .collect();
+    let candidates: Vec<String> = expected_thread_states(step)
+        .into_iter()
+        .map(|thread_state| format!("{{{}}}", thread_state.join(", ")))
+        .collect();
+
+    format!(
+        r#"{{"{:?}", {{{}}}, {}, {{{{{}}}}}, {}}}"#,
+        step.fn_type,
+        args.join(", "),
+        harness_tid(step.caller),
+        candidates.join(", "),
+        step.ret
+    )
+}
+
+// The constants of <pthread.h>
+fn harness_mutex_protocol(protocol: MutexProtocol) -> &'static str {
+    match protocol {
+        MutexProtocol::PrioNone => "PTHREAD_PRIO_NONE",
+        MutexProtocol::PrioInherit => "PTHREAD_PRIO_INHERIT",
+        MutexProtocol::PrioProtect => "PTHREAD_PRIO_PROTECT",
+    }
+}
+
+// The constants of <sched.h>
+fn harness_policy(policy: Policy) -> &'static str {
+    match policy {
+        Policy::Fifo => "SCHED_FIFO",
+        Policy::RoundRobin => "SCHED_RR",
+    }
+}
+
+// A translation unit defining the test_seq[] table that TestProgramGen/main.cpp runs
+pub fn gen_test_program(path: &[Step]) -> String {
+    let num_cores = path.first().map_or(0, |step| step.state.cpu.cores.len());
+    // The mutex as the harness initializes it, before any call changes its ceiling
+    let (mutex_protocol, mutex_prioceiling) = path
+        .first()
+        .and_then(|step| step.state.mutexes.first())
+        .map_or((MutexProtocol::PrioNone, sync::DEFAULT_CEILING), |mutex| {
+            (mutex.protocol, mutex.ceiling)
+        });
+    let config = path
+        .first()
+        .map_or(ModelConfig::default(), |step| step.state.config);
+    // The launch of the program is not a call made by the harness
+    let test_cases: Vec<String> = path
+        .iter()
+        .filter(|step| step.fn_type != Function::Spawn)
+        .map(gen_test_case)
+        .collect();
+
+    [
+        r#"#include "../TestProgramGen/util.h""#.to_string(),
+        format!("extern const int num_cores = {};", num_cores),
+        format!(
+            "extern const int mutex_protocol = {};",
+            harness_mutex_protocol(mutex_protocol)
+        ),
+        format!(
+            "extern const int mutex_prioceiling = {};",
+            mutex_prioceiling
+        ),
+        format!(
+            "extern const int main_policy = {};",
+            harness_policy(config.initial_policy)
+        ),
+        format!(
+            "extern const int main_priority = {};",
+            config.initial_priority
+        ),
+        format!("test_t test_seq[] = {{{}}};", test_cases.join(",\n")),
+        "size_t test_seq_size = sizeof(test_seq) / sizeof(test_t);".to_string(),
+    ]
+    .join("\n")
+        + "\n"
+}
+
+// Writes tp_0.cpp, tp_1.cpp, ... into the directory, one file per path
+pub fn write_test_programs(paths: &[Vec<Step>], dir: &Path) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+
+    let mut files = vec![];
+    for (i, path) in paths.iter().enumerate() {
+        let file = dir.join(format!("tp_{}.cpp", i));
+        fs::write(&file, gen_test_program(path))?;
+        files.push(file);
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expected_thread_states, gen_test_case, gen_test_program, thread_state};
+    use crate::errno;
+    use crate::oracle_tree::Step;
+    use crate::search::Explorer;
+    use crate::spec::{
+        function::{get_function, Function},
+        scheduler::State,
+    };
+
+    #[test]
+    fn test_gen_test_program() {
+        let tree = Explorer::new(1)
+            .max_depth(2)
+            .functions(vec![Function::PthreadMutexLock, Function::PthreadExit])
+            .explore();
+        let paths = tree.paths();
+        let path = paths
+            .iter()
+            .find(|path| {
+                path[1].fn_type == Function::PthreadMutexLock
+                    && path[2].fn_type == Function::PthreadMutexLock
+            })
+            .unwrap();
+
+        assert_eq!(thread_state(&path[0].state), vec!["RUNNING"]);
+        assert_eq!(
+            gen_test_program(path),
+            r#"#include "../TestProgramGen/util.h"
+extern const int num_cores = 1;
+extern const int mutex_protocol = PTHREAD_PRIO_NONE;
+extern const int mutex_prioceiling = 99;
+extern const int main_policy = SCHED_FIFO;
+extern const int main_priority = 1;
+test_t test_seq[] = {{"PthreadMutexLock", {0}, 0, {{{RUNNING}}}, 0},
+{"PthreadMutexLock", {0}, 0, {{{RUNNING}}}, 35}};
+size_t test_seq_size = sizeof(test_seq) / sizeof(test_t);
+"#
+        );
+
+        let path = paths
+            .iter()
+            .find(|path| path.len() == 2 && path[1].fn_type == Function::PthreadExit)
+            .unwrap();
+        assert_eq!(thread_state(&path[1].state), vec!["TERMINATED"]);
+    }
+
+    #[test]
+    fn test_expected_thread_states() {
+        let tree = Explorer::new(2)
+            .functions(vec![Function::PthreadCreate, Function::PthreadExit])
+            .explore();
+        let paths = tree.paths();
+
+        // The two launches place task 1 on different cores but look the same
+        assert_eq!(paths[0][0].group.len(), 2);
+        assert_eq!(expected_thread_states(&paths[0][0]), vec![vec!["RUNNING"]]);
+
+        // A second task of higher priority fills the idle core
+        let path = paths
+            .iter()
+            .find(|path| {
+                path[1].fn_type == Function::PthreadCreate && path[1].args == vec![2, 1, 0, 0]
+            })
+            .unwrap();
+        assert_eq!(
+            expected_thread_states(&path[1]),
+            vec![vec!["RUNNING", "RUNNING"]]
+        );
+        // A third task of the highest priority preempts either of them
+        let create = get_function(Function::PthreadCreate);
+        let state = paths
+            .iter()
+            .find(|path| {
+                path[1].fn_type == Function::PthreadCreate && path[1].args == vec![1, 1, 0, 0]
+            })
+            .map(|path| path[1].state.clone())
+            .unwrap();
+        let group: Vec<State> = create
+            .call(&state, 1, &[2, 1, 0, 0])
+            .into_iter()
+            .map(|t| t.next)
+            .collect();
+        let step = Step {
+            fn_type: Function::PthreadCreate,
+            caller: 1,
+            args: vec![2, 1, 0, 0],
+            ret: errno::EOK,
+            state: group[0].clone(),
+            group,
+        };
+        assert_eq!(
+            expected_thread_states(&step),
+            vec![
+                vec!["READY", "RUNNING", "RUNNING"],
+                vec!["RUNNING", "READY", "RUNNING"]
+            ]
+        );
+        assert!(gen_test_case(&step).contains(
+            r#"{"PthreadCreate", {2, 1, 0, 0}, 0, {{{READY, RUNNING, RUNNING}, {RUNNING, READY, RUNNING}}}, 0}"#
+        ));
+
+        // The target of pthread_setschedprio is given by its harness TID
+        let step = Step {
+            fn_type: Function::PthreadSetschedprio,
+            caller: 1,
+            args: vec![1, 5],
+            ret: errno::EOK,
+            state: paths[0][0].state.clone(),
+            group: vec![],
+        };
+        assert!(gen_test_case(&step).starts_with(r#"{"PthreadSetschedprio", {0, 5}, 0,"#));
+    }
+}
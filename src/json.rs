@@ -0,0 +1,143 @@
+use std::fmt;
+
+use crate::oracle_tree::Step;
+use crate::spec::scheduler;
+
+// A minimal JSON value for the machine-readable output of the command-line interface
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn obj(fields: Vec<(&str, Json)>) -> Json {
+        Json::Obj(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+}
+
+impl From<u32> for Json {
+    fn from(n: u32) -> Json {
+        Json::Num(n as f64)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Json {
+        Json::Num(n as f64)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Json {
+        Json::Str(s.to_string())
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(o: Option<T>) -> Json {
+        o.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(v: Vec<T>) -> Json {
+        Json::Arr(v.into_iter().map(Into::into).collect())
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Num(n) => write!(f, "{}", n),
+            Json::Str(s) => write_str(f, s),
+            Json::Arr(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Obj(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_str(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+// States are written in their one-line notation
+impl From<&scheduler::State> for Json {
+    fn from(state: &scheduler::State) -> Json {
+        Json::Str(state.to_string())
+    }
+}
+
+impl From<&Step> for Json {
+    fn from(step: &Step) -> Json {
+        Json::obj(vec![
+            ("function", format!("{:?}", step.fn_type).as_str().into()),
+            ("caller", step.caller.into()),
+            ("args", step.args.clone().into()),
+            ("ret", step.ret.into()),
+            ("state", (&step.state).into()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Json;
+    use crate::spec::scheduler::State;
+
+    #[test]
+    fn test_json() {
+        let json = Json::obj(vec![
+            ("a", vec![1_u32, 2].into()),
+            ("b", Json::from(None::<u32>)),
+            ("c", "x\"y".into()),
+        ]);
+        assert_eq!(json.to_string(), r#"{"a":[1,2],"b":null,"c":"x\"y"}"#);
+
+        let state = State::new(1).create_task(1).schedule().remove(0);
+        assert_eq!(
+            Json::from(&state).to_string(),
+            r#""C0:T1/p1 RQ:[] X:[] M0:-""#
+        );
+    }
+}
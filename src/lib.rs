@@ -0,0 +1,9 @@
+pub mod codegen;
+pub mod errno;
+pub mod json;
+pub mod observation;
+pub mod oracle_tree;
+pub mod scenario;
+pub mod search;
+pub mod spec;
+pub mod state_graph;
@@ -1,49 +1,348 @@
-mod oracle_tree;
-mod search;
-mod spec;
+use itertools::Itertools;
+use posix_sched_tester::codegen;
+use posix_sched_tester::json::Json;
+use posix_sched_tester::scenario::{Scenario, ScenarioError};
+use posix_sched_tester::search::Explorer;
+use posix_sched_tester::spec::{
+    config::ModelConfig, cpu::MAX_CORES, sched_data::Policy, sync::MutexProtocol,
+};
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::time::Instant;
 
-use spec::{function, scheduler};
+const USAGE: &str = "usage: posix-sched-tester <command> [options]
 
-fn main() {
-    let mut states = scheduler::State::new(2).create_task(1).schedule();
-
-    let mut new_states = vec![];
-    for state in states.into_iter() {
-        for new_state in function::get_function(function::Function::PthreadCreate)
-            .call(&state, 1, &[3])
-            .into_iter()
-        {
-            if !new_states.contains(&new_state) {
-                new_states.push(new_state);
-            }
+commands:
+  explore [options]          print every path of the oracle tree, one JSON array per line
+  replay SCENARIO            run the calls of a scenario file and print the resulting states
+  gen [options]              write one test program per path into --out
+  stats [options]            print the size of the explored state space
+
+options:
+  --cores N          number of CPU cores (default 1)
+  --depth D          number of calls after the launch (default 1)
+  --max-threads N    threads that may be created including the main thread (default 4)
+  --max-tid T        largest TID tried as a caller (default the thread limit)
+  --prio MIN:MAX     priorities passed to the functions
+  --fifo-prio R      priority range MIN:MAX of SCHED_FIFO (default 1:99)
+  --rr-prio R        priority range MIN:MAX of SCHED_RR (default 1:99)
+  --main-policy P    policy of the main thread, fifo or rr (default fifo)
+  --main-prio P      priority of the main thread (default 1)
+  --relative         pass only one priority per position relative to the existing priorities
+  --protocol P       protocol of the mutexes, none, inherit or protect (default none)
+  --ceiling C        priority ceiling of the mutexes under protect (default 99)
+  --symmetry         merge states that differ only in the placement on cores
+  --dot              (explore) print the tree in the DOT format instead
+  --out DIR          (gen) output directory (default tp)
+  --script TEXT      (replay) run the scenario given inline instead of a file
+
+A scenario reads e.g. \"cores 2; spawn; 1: pthread_create(3, 1, 0, 0); 3: pthread_exit()\"";
+
+struct Options {
+    num_core: u32,
+    max_depth: usize,
+    config: ModelConfig,
+    max_tid: Option<u32>,
+    priority_range: Option<(u32, u32)>,
+    relative_priorities: bool,
+    protocol: MutexProtocol,
+    ceiling: Option<u32>,
+    symmetry_reduction: bool,
+    dot: bool,
+    out: PathBuf,
+    script: Option<String>,
+    files: Vec<String>,
+}
+
+impl Options {
+    fn explorer(&self) -> Explorer {
+        let mut explorer = Explorer::new(self.num_core)
+            .config(self.config)
+            .max_depth(self.max_depth)
+            .mutex_protocol(self.protocol)
+            .symmetry_reduction(self.symmetry_reduction)
+            .relative_priorities(self.relative_priorities);
+        if let Some(max_tid) = self.max_tid {
+            explorer = explorer.max_tid(max_tid);
+        }
+        if let Some((min, max)) = self.priority_range {
+            explorer = explorer.priority_range(min, max);
+        }
+        if let Some(ceiling) = self.ceiling {
+            explorer = explorer.mutex_ceiling(ceiling);
         }
+        explorer
     }
-    states = new_states;
+}
+
+fn parse_value<T: std::str::FromStr>(option: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or(format!("{} needs a value", option))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", option, value))
+}
 
-    let mut new_states = vec![];
-    for state in states.into_iter() {
-        for new_state in function::get_function(function::Function::PthreadCreate)
-            .call(&state, 1, &[3])
-            .into_iter()
-        {
-            if !new_states.contains(&new_state) {
-                new_states.push(new_state);
+// A range of priorities written as MIN:MAX with 1 <= MIN <= MAX
+fn parse_range(option: &str, value: Option<String>) -> Result<(u32, u32), String> {
+    let value: String = parse_value(option, value)?;
+    let (min, max) = value
+        .split_once(':')
+        .ok_or(format!("invalid value for {}: {}", option, value))?;
+    let (min, max) = (
+        parse_value(option, Some(min.to_string()))?,
+        parse_value(option, Some(max.to_string()))?,
+    );
+    if min == 0 || min > max {
+        return Err(format!("{} needs 1 <= MIN <= MAX, found {}", option, value));
+    }
+    Ok((min, max))
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        num_core: 1,
+        max_depth: 1,
+        config: ModelConfig::default(),
+        max_tid: None,
+        priority_range: None,
+        relative_priorities: false,
+        protocol: MutexProtocol::PrioNone,
+        ceiling: None,
+        symmetry_reduction: false,
+        dot: false,
+        out: PathBuf::from("tp"),
+        script: None,
+        files: vec![],
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cores" => options.num_core = parse_value(&arg, args.next())?,
+            "--depth" => options.max_depth = parse_value(&arg, args.next())?,
+            "--max-threads" => {
+                options.config.max_threads = parse_value(&arg, args.next())?;
+                if options.config.max_threads == 0 {
+                    return Err("--max-threads must be at least 1".to_string());
+                }
+            }
+            "--max-tid" => options.max_tid = Some(parse_value(&arg, args.next())?),
+            "--prio" => options.priority_range = Some(parse_range(&arg, args.next())?),
+            "--fifo-prio" => options.config.fifo_priority_range = parse_range(&arg, args.next())?,
+            "--rr-prio" => options.config.rr_priority_range = parse_range(&arg, args.next())?,
+            "--main-policy" => {
+                let value: String = parse_value(&arg, args.next())?;
+                options.config.initial_policy = match value.as_str() {
+                    "fifo" => Policy::Fifo,
+                    "rr" => Policy::RoundRobin,
+                    _ => return Err(format!("invalid value for {}: {}", arg, value)),
+                };
             }
+            "--main-prio" => options.config.initial_priority = parse_value(&arg, args.next())?,
+            "--relative" => options.relative_priorities = true,
+            "--protocol" => options.protocol = parse_value(&arg, args.next())?,
+            "--ceiling" => options.ceiling = Some(parse_value(&arg, args.next())?),
+            "--symmetry" => options.symmetry_reduction = true,
+            "--dot" => options.dot = true,
+            "--out" => options.out = parse_value(&arg, args.next())?,
+            "--script" => options.script = Some(parse_value(&arg, args.next())?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+            _ => options.files.push(arg),
         }
     }
-    states = new_states;
+    if options.num_core == 0 || options.num_core > MAX_CORES {
+        return Err(format!("--cores must be between 1 and {}", MAX_CORES));
+    }
+    let config = options.config;
+    if !config.is_valid_priority(config.initial_policy, config.initial_priority) {
+        let (option, (min, max)) = match config.initial_policy {
+            Policy::Fifo => ("--fifo-prio", config.fifo_priority_range),
+            Policy::RoundRobin => ("--rr-prio", config.rr_priority_range),
+        };
+        return Err(format!(
+            "--main-prio {} is out of the range {}:{} of {}",
+            config.initial_priority, min, max, option
+        ));
+    }
+    Ok(options)
+}
 
-    let mut new_states = vec![];
-    for state in states.into_iter() {
-        for new_state in function::get_function(function::Function::PthreadExit)
-            .call(&state, 3, &[])
-            .into_iter()
-        {
-            if !new_states.contains(&new_state) {
-                new_states.push(new_state);
-            }
+fn explore(options: &Options) {
+    let tree = options.explorer().explore();
+    if options.dot {
+        print!("{}", tree.to_dot());
+        return;
+    }
+    for path in tree.paths() {
+        println!("{}", Json::Arr(path.iter().map(Json::from).collect()));
+    }
+}
+
+// Prints every statement of the scenario and the set of states after it
+fn replay(options: &Options) -> Result<(), String> {
+    let text = match (&options.script, options.files.as_slice()) {
+        (Some(script), []) => script.clone(),
+        (None, [file]) => fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?,
+        _ => return Err("replay takes one scenario file or --script".to_string()),
+    };
+    let scenario: Scenario = text.parse().map_err(|e: ScenarioError| e.to_string())?;
+    let trace = scenario.trace(options.config).map_err(|e| e.to_string())?;
+
+    for (statement, transitions) in scenario.statements.iter().zip(trace) {
+        println!(
+            "{}",
+            Json::obj(vec![
+                ("line", statement.line.into()),
+                ("call", statement.to_string().as_str().into()),
+                (
+                    "rets",
+                    transitions
+                        .iter()
+                        .map(|t| t.ret)
+                        .unique()
+                        .collect::<Vec<_>>()
+                        .into()
+                ),
+                (
+                    "states",
+                    transitions
+                        .iter()
+                        .map(|t| &t.next)
+                        .unique()
+                        .collect::<Vec<_>>()
+                        .into()
+                ),
+            ])
+        );
+    }
+    Ok(())
+}
+
+fn gen(options: &Options) -> Result<(), String> {
+    let paths = options.explorer().explore().paths();
+    let files = codegen::write_test_programs(&paths, &options.out)
+        .map_err(|e| format!("{}: {}", options.out.display(), e))?;
+    println!(
+        "{}",
+        Json::obj(vec![(
+            "files",
+            files
+                .iter()
+                .map(|file| file.display().to_string())
+                .map(Json::Str)
+                .collect::<Vec<_>>()
+                .into()
+        )])
+    );
+    Ok(())
+}
+
+fn stats(options: &Options) {
+    let explorer = options.explorer();
+    let start = Instant::now();
+    let tree = explorer.explore();
+    let graph = explorer.explore_graph();
+    let elapsed = start.elapsed();
+
+    println!(
+        "{}",
+        Json::obj(vec![
+            ("cores", options.num_core.into()),
+            ("depth", options.max_depth.into()),
+            ("tree_nodes", tree.count_nodes().into()),
+            ("paths", tree.paths().len().into()),
+            ("graph_states", graph.num_states().into()),
+            ("graph_edges", graph.num_edges().into()),
+            ("time_ms", Json::Num(elapsed.as_secs_f64() * 1000.0)),
+            (
+                "physical_mem_bytes",
+                memory_stats::memory_stats()
+                    .map(|usage| usage.physical_mem)
+                    .into()
+            ),
+        ])
+    );
+}
+
+// Only a wrong command line is answered with the usage
+fn usage_error(message: String) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    let run: fn(&Options) -> Result<(), String> = match command.as_deref() {
+        Some("explore") => |options| {
+            explore(options);
+            Ok(())
+        },
+        Some("replay") => replay,
+        Some("gen") => gen,
+        Some("stats") => |options| {
+            stats(options);
+            Ok(())
+        },
+        Some(command) => usage_error(format!("unknown command: {}", command)),
+        None => usage_error("no command given".to_string()),
+    };
+    let options = parse_options(args).unwrap_or_else(|message| usage_error(message));
+
+    if let Err(message) = run(&options) {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_options;
+    use posix_sched_tester::spec::sched_data::Policy;
+
+    fn parse(args: &str) -> Result<super::Options, String> {
+        parse_options(args.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options =
+            parse("--cores 2 --max-threads 3 --rr-prio 2:4 --main-policy rr --main-prio 3")
+                .ok()
+                .unwrap();
+        assert_eq!(options.num_core, 2);
+        assert_eq!(options.config.max_threads, 3);
+        assert_eq!(options.config.rr_priority_range, (2, 4));
+        assert_eq!(options.config.initial_policy, Policy::RoundRobin);
+        assert_eq!(options.config.initial_priority, 3);
+
+        for (args, message) in [
+            ("--cores 0", "--cores must be between 1 and 32"),
+            ("--cores 33", "--cores must be between 1 and 32"),
+            ("--max-threads 0", "--max-threads must be at least 1"),
+            (
+                "--fifo-prio 3:1",
+                "--fifo-prio needs 1 <= MIN <= MAX, found 3:1",
+            ),
+            (
+                "--rr-prio 0:5",
+                "--rr-prio needs 1 <= MIN <= MAX, found 0:5",
+            ),
+            ("--prio 5", "invalid value for --prio: 5"),
+            (
+                "--main-policy other",
+                "invalid value for --main-policy: other",
+            ),
+            (
+                "--fifo-prio 2:4",
+                "--main-prio 1 is out of the range 2:4 of --fifo-prio",
+            ),
+            (
+                "--main-policy rr --main-prio 50 --rr-prio 1:10",
+                "--main-prio 50 is out of the range 1:10 of --rr-prio",
+            ),
+        ] {
+            assert_eq!(parse(args).err().unwrap(), message, "{}", args);
         }
     }
-    states = new_states;
-    println!("{:#?}", states);
 }
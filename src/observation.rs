@@ -0,0 +1,122 @@
+use crate::spec::{sched_data::TaskState, scheduler};
+
+// What the harness can measure besides the run state of each thread. The run state and the
+// priority are read from /proc/<pid>/task/<tid>/stat, while the core requires extra work
+// and the order of the ready queue cannot be observed at all.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Observability {
+    pub core_ids: bool,
+    pub priority: bool,
+}
+
+impl Observability {
+    pub const FULL: Observability = Observability {
+        core_ids: true,
+        priority: true,
+    };
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ThreadObservation {
+    pub tid: u32,
+    pub state: TaskState,
+    pub core: Option<u32>,
+    pub prio: Option<u32>,
+}
+
+// The projection of a scheduler state onto what the harness observes, sorted by TID
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Observation(pub Vec<ThreadObservation>);
+
+impl Observation {
+    pub fn of(state: &scheduler::State, level: Observability) -> Self {
+        let mut threads: Vec<ThreadObservation> = state
+            .tasks()
+            .map(|task| ThreadObservation {
+                tid: task.tid,
+                state: task.state,
+                core: None,
+                prio: if level.priority {
+                    Some(task.prio)
+                } else {
+                    None
+                },
+            })
+            .collect();
+
+        if level.core_ids {
+            for core in state.cpu.cores.iter() {
+                if let Some(task) = &core.task {
+                    let thread = threads.iter_mut().find(|t| t.tid == task.tid).unwrap();
+                    thread.core = Some(core.id);
+                }
+            }
+        }
+
+        threads.sort_by_key(|thread| thread.tid);
+        Observation(threads)
+    }
+
+    pub fn get(&self, tid: u32) -> Option<&ThreadObservation> {
+        self.0.iter().find(|thread| thread.tid == tid)
+    }
+}
+
+// Groups the states that look the same under the observability level, in order of first appearance
+pub fn merge<'a, I>(
+    states: I,
+    level: Observability,
+) -> Vec<(Observation, Vec<&'a scheduler::State>)>
+where
+    I: IntoIterator<Item = &'a scheduler::State>,
+{
+    let mut groups: Vec<(Observation, Vec<&scheduler::State>)> = vec![];
+    for state in states.into_iter() {
+        let observation = Observation::of(state, level);
+        match groups.iter_mut().find(|(o, _)| *o == observation) {
+            Some((_, group)) => group.push(state),
+            None => groups.push((observation, vec![state])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge, Observability, Observation, ThreadObservation};
+    use crate::spec::{sched_data::TaskState, scheduler::State};
+
+    #[test]
+    fn test_observation() {
+        let states = State::new(2)
+            .create_task(1)
+            .create_task(1)
+            .create_task(2)
+            .schedule();
+        // Tasks 3 and 1 run on either core while task 2 is ready
+        assert_eq!(states.len(), 2);
+
+        let observation = Observation::of(&states[0], Observability::FULL);
+        assert_eq!(observation.0.len(), 3);
+        let thread = observation.get(3).unwrap();
+        assert_eq!(thread.state, TaskState::Running);
+        assert_eq!(thread.prio, Some(2));
+        assert!(thread.core.is_some());
+
+        assert_eq!(
+            Observation::of(&states[0], Observability::default()).get(3),
+            Some(&ThreadObservation {
+                tid: 3,
+                state: TaskState::Running,
+                core: None,
+                prio: None,
+            })
+        );
+
+        assert_eq!(merge(&states, Observability::FULL).len(), 2);
+        let groups = merge(&states, Observability::default());
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].1.len(), 2);
+        assert_eq!(groups[0].0.get(2).unwrap().state, TaskState::Ready);
+    }
+}
@@ -1,10 +1,7 @@
 use crate::spec::{
-    cpu::CPU,
-    function::{get_function, Function},
-    sched_data::ReadyQueue,
+    function::{get_function, Function, Transition},
     scheduler,
 };
-use spin::mutex::SpinMutex;
 
 type NodeGroup = Vec<Node>;
 
@@ -15,41 +12,19 @@ pub struct OracleTree {
 }
 
 impl OracleTree {
-    const fn new() -> Self {
-        OracleTree {
-            root: Node {
-                expected_state: scheduler::State {
-                    cpu: CPU::const_new(),
-                    ready_queue: ReadyQueue::new(),
-                    terminated_tasks: vec![],
-                },
-                edges: vec![],
-            },
-        }
+    // A tree whose only edges are the launch of the test program on `num_core` cores
+    pub fn init(num_core: u32) -> Self {
+        let initial_state = scheduler::State::new(num_core);
+        let transitions = get_function(Function::Spawn).call(&initial_state, 0, &[]);
+        OracleTree::from_spawn(initial_state, transitions)
     }
 
-    pub fn init(num_core: u32) {
-        let spawn = get_function(Function::Spawn);
-        let states = spawn.call(&scheduler::State::new(num_core), 0, &[]);
-        let node_group = {
-            let mut v = vec![];
-            for state in states.into_iter() {
-                v.push(Node {
-                    expected_state: state,
-                    edges: vec![],
-                });
-            }
-            v
-        };
-
-        let mut tree = ORACLE_TREE.lock();
-        let root: &mut Node = &mut tree.root;
-
-        root.add_edge(Edge {
-            fn_type: Function::Spawn,
-            args: vec![],
-            node_group,
-        });
+    pub fn from_spawn(initial_state: scheduler::State, transitions: Vec<Transition>) -> Self {
+        let mut root = Node::new(initial_state);
+        for edge in Edge::from_transitions(Function::Spawn, 0, vec![], transitions).into_iter() {
+            root.add_edge(edge);
+        }
+        OracleTree { root }
     }
 
     pub fn get_init_nodes(&self) -> Vec<&Node> {
@@ -59,6 +34,89 @@ impl OracleTree {
         }
         v
     }
+
+    pub fn get_init_nodes_mut(&mut self) -> Vec<&mut Node> {
+        self.root
+            .edges
+            .iter_mut()
+            .flat_map(|edge| edge.node_group.iter_mut())
+            .collect()
+    }
+
+    pub fn get_root(&self) -> &Node {
+        &self.root
+    }
+
+    // Visits every node below the root in depth-first order together with the path leading to it
+    pub fn walk<F: FnMut(&[(&Edge, &Node)])>(&self, visit: &mut F) {
+        fn walk_from<'a, F: FnMut(&[(&Edge, &Node)])>(
+            node: &'a Node,
+            path: &mut Vec<(&'a Edge, &'a Node)>,
+            visit: &mut F,
+        ) {
+            for edge in node.edges.iter() {
+                for next in edge.node_group.iter() {
+                    path.push((edge, next));
+                    visit(path);
+                    walk_from(next, path, visit);
+                    path.pop();
+                }
+            }
+        }
+
+        walk_from(&self.root, &mut vec![], visit);
+    }
+
+    pub fn count_nodes(&self) -> usize {
+        let mut count = 0;
+        self.walk(&mut |_| count += 1);
+        count
+    }
+
+    // Every root-to-leaf path as a sequence of steps
+    pub fn paths(&self) -> Vec<Vec<Step>> {
+        let mut paths = vec![];
+        self.walk(&mut |path| {
+            let (_, last) = path[path.len() - 1];
+            if last.edges.is_empty() {
+                paths.push(
+                    path.iter()
+                        .map(|(edge, node)| Step::new(edge, node))
+                        .collect(),
+                );
+            }
+        });
+        paths
+    }
+
+    // Exports the tree in the Graphviz DOT format
+    pub fn to_dot(&self) -> String {
+        fn node_to_dot(node: &Node, id: usize, num_nodes: &mut usize, lines: &mut Vec<String>) {
+            for edge in node.edges.iter() {
+                for next in edge.node_group.iter() {
+                    let next_id = *num_nodes;
+                    *num_nodes += 1;
+                    lines.push(format!(
+                        "  n{} [label=\"{:?}\"];",
+                        next_id, next.expected_state
+                    ));
+                    lines.push(format!(
+                        "  n{} -> n{} [label=\"{}: {:?}{:?} = {}\"];",
+                        id, next_id, edge.caller, edge.fn_type, edge.args, edge.ret
+                    ));
+                    node_to_dot(next, next_id, num_nodes, lines);
+                }
+            }
+        }
+
+        let mut lines = vec![
+            "digraph oracle_tree {".to_string(),
+            "  n0 [label=\"root\"];".to_string(),
+        ];
+        node_to_dot(&self.root, 0, &mut 1, &mut lines);
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -91,83 +149,99 @@ impl Node {
 #[derive(Debug, PartialEq, Eq)]
 pub struct Edge {
     pub fn_type: Function,
+    pub caller: u32,
     pub args: Vec<u32>,
+    // The value returned by the function, either EOK or an errno
+    pub ret: usize,
     pub node_group: NodeGroup,
 }
 
-pub static ORACLE_TREE: SpinMutex<OracleTree> = SpinMutex::new(OracleTree::new());
+impl Edge {
+    // Makes one edge per distinct return value, keeping the order of the transitions
+    pub fn from_transitions(
+        fn_type: Function,
+        caller: u32,
+        args: Vec<u32>,
+        transitions: Vec<Transition>,
+    ) -> Vec<Edge> {
+        let mut edges: Vec<Edge> = vec![];
+        for Transition { ret, next } in transitions.into_iter() {
+            let node = Node::new(next);
+            match edges.iter_mut().find(|edge| edge.ret == ret) {
+                Some(edge) => edge.node_group.push(node),
+                None => edges.push(Edge {
+                    fn_type,
+                    caller,
+                    args: args.clone(),
+                    ret,
+                    node_group: vec![node],
+                }),
+            }
+        }
+        edges
+    }
+}
+
+// A function call on a path of the tree and the state it reached on that path
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub fn_type: Function,
+    pub caller: u32,
+    pub args: Vec<u32>,
+    pub ret: usize,
+    pub state: scheduler::State,
+    // Every state the call may lead to, including `state`
+    pub group: Vec<scheduler::State>,
+}
+
+impl Step {
+    pub fn new(edge: &Edge, node: &Node) -> Self {
+        Step {
+            fn_type: edge.fn_type,
+            caller: edge.caller,
+            args: edge.args.clone(),
+            ret: edge.ret,
+            state: node.expected_state.clone(),
+            group: edge
+                .node_group
+                .iter()
+                .map(|n| n.expected_state.clone())
+                .collect(),
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
-    use super::{Edge, Node, OracleTree, ORACLE_TREE};
-    use crate::spec::{
-        cpu::{Core, CPU},
-        function::Function::Spawn,
-        sched_data::{ReadyQueue, TaskControlBlock, TaskState::Running},
-        scheduler::State,
-    };
+    use super::{Edge, Node, OracleTree};
+    use crate::errno;
+    use crate::spec::function::Function::Spawn;
+
+    fn node(state: &str) -> Node {
+        Node::new(state.parse().unwrap())
+    }
 
     #[test]
     fn test_oracle_tree_init() {
-        OracleTree::init(2);
-        let tree = ORACLE_TREE.lock();
-        assert_eq!(
-            *tree,
-            OracleTree {
-                root: Node {
-                    expected_state: State {
-                        cpu: CPU { cores: vec![] },
-                        ready_queue: ReadyQueue::new(),
-                        terminated_tasks: vec![],
-                    },
-                    edges: vec![Edge {
-                        fn_type: Spawn,
-                        args: vec![],
-                        node_group: vec![
-                            Node {
-                                expected_state: State {
-                                    cpu: CPU {
-                                        cores: vec![
-                                            Core {
-                                                id: 0,
-                                                task: Some(TaskControlBlock {
-                                                    tid: 1,
-                                                    prio: 1,
-                                                    state: Running,
-                                                },),
-                                            },
-                                            Core { id: 1, task: None },
-                                        ],
-                                    },
-                                    ready_queue: ReadyQueue::new(),
-                                    terminated_tasks: vec![],
-                                },
-                                edges: vec![],
-                            },
-                            Node {
-                                expected_state: State {
-                                    cpu: CPU {
-                                        cores: vec![
-                                            Core { id: 0, task: None },
-                                            Core {
-                                                id: 1,
-                                                task: Some(TaskControlBlock {
-                                                    tid: 1,
-                                                    prio: 1,
-                                                    state: Running,
-                                                },),
-                                            },
-                                        ],
-                                    },
-                                    ready_queue: ReadyQueue::new(),
-                                    terminated_tasks: vec![],
-                                },
-                                edges: vec![],
-                            },
-                        ],
-                    },],
-                },
-            }
-        );
+        let tree = OracleTree::init(2);
+        let mut root = node("C0:- C1:- RQ:[] X:[] M0:-");
+        root.add_edge(Edge {
+            fn_type: Spawn,
+            caller: 0,
+            args: vec![],
+            ret: errno::EOK,
+            node_group: vec![
+                node("C0:T1/p1 C1:- RQ:[] X:[] M0:-"),
+                node("C0:- C1:T1/p1 RQ:[] X:[] M0:-"),
+            ],
+        });
+        assert_eq!(tree, OracleTree { root });
+    }
+
+    #[test]
+    fn test_oracle_tree_init_is_independent() {
+        assert_eq!(OracleTree::init(2), OracleTree::init(2));
+        assert_eq!(OracleTree::init(2).count_nodes(), 2);
+        assert_eq!(OracleTree::init(3).count_nodes(), 3);
     }
 }
@@ -0,0 +1,414 @@
+use std::fmt;
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use crate::spec::{
+    config::ModelConfig,
+    cpu::MAX_CORES,
+    function::{get_function, Function, Transition},
+    scheduler,
+    sync::{self, MutexProtocol},
+};
+
+// A call sequence written as text, e.g. "cores 2; spawn; 1: pthread_create(3, 1, 0, 0); 3: pthread_exit()".
+// Statements are separated by ';' or newlines and '#' starts a comment. "protocol inherit"
+// initializes the mutexes with PTHREAD_PRIO_INHERIT, and "protocol protect 5" with
+// PTHREAD_PRIO_PROTECT and the ceiling 5. "prune" lets a call drop the states in which it is not
+// invokable instead of failing. The model config is not part of the text and is given when the
+// scenario is run
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scenario {
+    pub num_core: u32,
+    pub protocol: MutexProtocol,
+    pub ceiling: u32,
+    pub prune: bool,
+    pub statements: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statement {
+    pub line: usize,
+    pub fn_type: Function,
+    pub caller: u32,
+    pub args: Vec<u32>,
+}
+
+// Where and why a scenario could not be parsed or run
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScenarioError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+fn error(line: usize, message: String) -> ScenarioError {
+    ScenarioError { line, message }
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.fn_type == Function::Spawn {
+            return write!(f, "spawn");
+        }
+        write!(
+            f,
+            "{}: {}({})",
+            self.caller,
+            self.fn_type,
+            self.args.iter().join(", ")
+        )
+    }
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cores {}", self.num_core)?;
+        match self.protocol {
+            MutexProtocol::PrioNone => {}
+            MutexProtocol::PrioInherit => write!(f, "; protocol {}", self.protocol)?,
+            MutexProtocol::PrioProtect => {
+                write!(f, "; protocol {} {}", self.protocol, self.ceiling)?
+            }
+        }
+        if self.prune {
+            write!(f, "; prune")?;
+        }
+        for statement in self.statements.iter() {
+            write!(f, "; {}", statement)?;
+        }
+        Ok(())
+    }
+}
+
+// e.g.) "1: pthread_create(3, 1, 0, 0)" |-> (PthreadCreate, 1, [3, 1, 0, 0])
+fn parse_call(line: usize, text: &str) -> Result<Statement, ScenarioError> {
+    let invalid = || {
+        error(
+            line,
+            format!("expected CALLER: FUNCTION(ARGS), found `{}`", text),
+        )
+    };
+    let (caller, call) = text.split_once(':').ok_or_else(invalid)?;
+    let (name, args) = call
+        .trim()
+        .strip_suffix(')')
+        .and_then(|call| call.split_once('('))
+        .ok_or_else(invalid)?;
+
+    let caller = caller
+        .trim()
+        .parse()
+        .map_err(|_| error(line, format!("invalid caller `{}`", caller.trim())))?;
+    let fn_type: Function = name
+        .trim()
+        .parse()
+        .map_err(|_| error(line, format!("unknown function `{}`", name.trim())))?;
+    let args = args
+        .split(',')
+        .map(str::trim)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| {
+            arg.parse()
+                .map_err(|_| error(line, format!("invalid argument `{}`", arg)))
+        })
+        .collect::<Result<Vec<u32>, _>>()?;
+
+    let arity = get_function(fn_type).params().len();
+    if fn_type == Function::Spawn || args.len() != arity {
+        return Err(error(
+            line,
+            format!("{} takes {} argument(s), found `{}`", fn_type, arity, text),
+        ));
+    }
+    Ok(Statement {
+        line,
+        fn_type,
+        caller,
+        args,
+    })
+}
+
+impl FromStr for Scenario {
+    type Err = ScenarioError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut scenario = Scenario {
+            num_core: 1,
+            protocol: MutexProtocol::PrioNone,
+            ceiling: sync::DEFAULT_CEILING,
+            prune: false,
+            statements: vec![],
+        };
+        let mut cores_given = false;
+
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.split('#').next().unwrap();
+            for text in line.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+                let words: Vec<&str> = text.split_whitespace().collect();
+                match words.as_slice() {
+                    ["cores", n] => {
+                        if cores_given || !scenario.statements.is_empty() {
+                            return Err(error(
+                                line_no,
+                                "`cores` must be given once before any call".to_string(),
+                            ));
+                        }
+                        scenario.num_core = n
+                            .parse()
+                            .ok()
+                            .filter(|&n| n > 0 && n <= MAX_CORES)
+                            .ok_or_else(|| error(line_no, format!("invalid core count `{}`", n)))?;
+                        cores_given = true;
+                    }
+                    ["protocol", protocol, ceiling @ ..] => {
+                        if !scenario.statements.is_empty() {
+                            return Err(error(
+                                line_no,
+                                "`protocol` must be given before any call".to_string(),
+                            ));
+                        }
+                        scenario.protocol = protocol.parse().map_err(|_| {
+                            error(line_no, format!("unknown protocol `{}`", protocol))
+                        })?;
+                        // Only priority protection takes a ceiling
+                        match (scenario.protocol, ceiling) {
+                            (MutexProtocol::PrioProtect, [ceiling]) => {
+                                scenario.ceiling = ceiling.parse().map_err(|_| {
+                                    error(line_no, format!("invalid ceiling `{}`", ceiling))
+                                })?;
+                            }
+                            (MutexProtocol::PrioProtect, _) | (_, [_, ..]) => {
+                                return Err(error(
+                                    line_no,
+                                    format!(
+                                        "expected `protocol protect CEILING`, found `{}`",
+                                        text
+                                    ),
+                                ));
+                            }
+                            _ => {}
+                        }
+                    }
+                    ["prune"] => {
+                        if !scenario.statements.is_empty() {
+                            return Err(error(
+                                line_no,
+                                "`prune` must be given before any call".to_string(),
+                            ));
+                        }
+                        scenario.prune = true;
+                    }
+                    ["spawn"] => scenario.statements.push(Statement {
+                        line: line_no,
+                        fn_type: Function::Spawn,
+                        caller: 0,
+                        args: vec![],
+                    }),
+                    _ => scenario.statements.push(parse_call(line_no, text)?),
+                }
+            }
+        }
+        Ok(scenario)
+    }
+}
+
+impl Scenario {
+    // The transitions of every statement, taken from every state the previous one led to.
+    // It is an error if the call is not invokable in some of the states, unless the scenario
+    // prunes them, and always if it is invokable in none
+    pub fn trace(&self, config: ModelConfig) -> Result<Vec<Vec<Transition>>, ScenarioError> {
+        let mut states = vec![self.initial_state(config)];
+        let mut trace = vec![];
+
+        for statement in self.statements.iter() {
+            let f = get_function(statement.fn_type);
+            let (invokable, rejecting): (Vec<_>, Vec<_>) = states.iter().partition(|state| {
+                statement.fn_type == Function::Spawn
+                    || f.is_invokable(state, statement.caller, &statement.args)
+            });
+            if invokable.is_empty() {
+                return Err(error(
+                    statement.line,
+                    format!(
+                        "`{}` is not invokable in any of the {} state(s)",
+                        statement,
+                        states.len()
+                    ),
+                ));
+            }
+            if !rejecting.is_empty() && !self.prune {
+                return Err(error(
+                    statement.line,
+                    format!(
+                        "`{}` is not invokable in {} of the {} state(s), e.g. {}; add `prune` to drop them",
+                        statement,
+                        rejecting.len(),
+                        states.len(),
+                        rejecting[0]
+                    ),
+                ));
+            }
+            let transitions: Vec<Transition> = invokable
+                .into_iter()
+                .flat_map(|state| f.call(state, statement.caller, &statement.args))
+                .unique()
+                .collect();
+
+            states = transitions
+                .iter()
+                .map(|t| t.next.clone())
+                .unique()
+                .collect();
+            trace.push(transitions);
+        }
+        Ok(trace)
+    }
+
+    // The set of states the scenario may end in
+    pub fn run(&self, config: ModelConfig) -> Result<Vec<scheduler::State>, ScenarioError> {
+        Ok(match self.trace(config)?.pop() {
+            Some(transitions) => transitions.into_iter().map(|t| t.next).unique().collect(),
+            None => vec![self.initial_state(config)],
+        })
+    }
+
+    fn initial_state(&self, config: ModelConfig) -> scheduler::State {
+        scheduler::State::new(self.num_core)
+            .with_config(config)
+            .with_mutex_protocol(self.protocol)
+            .with_mutex_ceiling(self.ceiling)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scenario;
+    use crate::errno;
+    use crate::spec::{config::ModelConfig, function::Function, sync::MutexProtocol};
+
+    #[test]
+    fn test_parse() {
+        let scenario: Scenario =
+            "cores 2; spawn # launch\n1: pthread_create(3, 1, 0, 0)\n3: pthread_exit()"
+                .parse()
+                .unwrap();
+        assert_eq!(scenario.num_core, 2);
+        assert_eq!(
+            scenario
+                .statements
+                .iter()
+                .map(|s| (s.line, s.fn_type, s.caller, s.args.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, Function::Spawn, 0, vec![]),
+                (2, Function::PthreadCreate, 1, vec![3, 1, 0, 0]),
+                (3, Function::PthreadExit, 3, vec![]),
+            ]
+        );
+        assert_eq!(
+            scenario.to_string(),
+            "cores 2; spawn; 1: pthread_create(3, 1, 0, 0); 3: pthread_exit()"
+        );
+        assert_eq!(
+            scenario
+                .to_string()
+                .parse::<Scenario>()
+                .unwrap()
+                .statements
+                .len(),
+            3
+        );
+
+        let scenario: Scenario = "cores 1; protocol inherit; spawn".parse().unwrap();
+        assert_eq!(scenario.protocol, MutexProtocol::PrioInherit);
+        assert_eq!(scenario.to_string(), "cores 1; protocol inherit; spawn");
+        let scenario: Scenario = "protocol protect 5".parse().unwrap();
+        assert_eq!(scenario.ceiling, 5);
+        assert_eq!(scenario.to_string(), "cores 1; protocol protect 5");
+        let scenario: Scenario = "prune; spawn".parse().unwrap();
+        assert!(scenario.prune);
+        assert_eq!(scenario.to_string(), "cores 1; prune; spawn");
+
+        for (text, line) in [
+            ("spawn; cores 2", 1),
+            ("cores 33", 1),
+            ("spawn\n1: pthread_frobnicate(2)", 2),
+            ("spawn; 1: pthread_create()", 1),
+            ("spawn; x: pthread_exit()", 1),
+            ("spawn; 1 pthread_exit()", 1),
+            ("protocol ceiling", 1),
+            ("protocol protect", 1),
+            ("protocol inherit 5", 1),
+            ("spawn\nprotocol inherit", 2),
+            ("spawn\nprune", 2),
+        ] {
+            assert_eq!(text.parse::<Scenario>().unwrap_err().line, line, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let trace = "cores 1; spawn; 1: pthread_mutex_lock(0); 1: pthread_mutex_lock(0)"
+            .parse::<Scenario>()
+            .unwrap()
+            .trace(ModelConfig::default())
+            .unwrap();
+        assert_eq!(trace.len(), 3);
+        assert_eq!(trace[2][0].ret, errno::EDEADLK);
+
+        let err = "cores 1\nspawn\n2: pthread_exit()"
+            .parse::<Scenario>()
+            .unwrap()
+            .run(ModelConfig::default())
+            .unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(
+            err.to_string(),
+            "line 3: `2: pthread_exit()` is not invokable in any of the 1 state(s)"
+        );
+
+        // T2 runs only where T1 was spawned on core 1, so the exit is rejected by the other state
+        let text = "cores 2\nspawn\n1: pthread_create(1, 1, 0, 1)\n2: pthread_exit()";
+        let err = text
+            .parse::<Scenario>()
+            .unwrap()
+            .run(ModelConfig::default())
+            .unwrap_err();
+        assert_eq!(err.line, 4);
+        assert_eq!(
+            err.to_string(),
+            "line 4: `2: pthread_exit()` is not invokable in 1 of the 2 state(s), \
+             e.g. C0:T1/p1 C1:- RQ:[T2/p1/a1] X:[] M0:-; add `prune` to drop them"
+        );
+        let states = format!("prune\n{}", text)
+            .parse::<Scenario>()
+            .unwrap()
+            .run(ModelConfig::default())
+            .unwrap();
+        assert_eq!(
+            states
+                .iter()
+                .map(|state| state.to_string())
+                .collect::<Vec<_>>(),
+            vec!["C0:- C1:T1/p1 RQ:[] X:[T2/p1/a1] M0:-"]
+        );
+
+        // The scenario runs under the config it is given
+        let config = ModelConfig {
+            initial_priority: 5,
+            ..ModelConfig::default()
+        };
+        let states = "spawn".parse::<Scenario>().unwrap().run(config).unwrap();
+        assert_eq!(states[0].to_string(), "C0:T1/p5 RQ:[] X:[] M0:-");
+        assert_eq!(states[0].config, config);
+    }
+}
@@ -1,52 +1,485 @@
-use itertools::iproduct;
+use itertools::{iproduct, Itertools};
 use strum::IntoEnumIterator;
 
-use crate::function::{get_function, Function};
-use crate::oracle_tree::{Edge, Node, OracleTree, ORACLE_TREE};
+use crate::oracle_tree::{Edge, Node, OracleTree};
+use crate::spec::{
+    config::ModelConfig,
+    function::{get_function, Function, Param, Transition},
+    scheduler,
+    sync::{self, MutexProtocol},
+};
+use crate::state_graph::StateGraph;
 
-// TODO
-const MAX_TID: u32 = 4;
+// Owns the configuration of one exploration so that several trees can be built independently
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explorer {
+    num_core: u32,
+    config: ModelConfig,
+    max_tid: Option<u32>,
+    max_depth: usize,
+    priority_range: Option<(u32, u32)>,
+    mutex_protocol: MutexProtocol,
+    mutex_ceiling: u32,
+    functions: Vec<Function>,
+    symmetry_reduction: bool,
+    relative_priorities: bool,
+}
+
+impl Explorer {
+    pub fn new(num_core: u32) -> Self {
+        Explorer {
+            num_core,
+            config: ModelConfig::default(),
+            max_tid: None,
+            max_depth: 1,
+            priority_range: None,
+            mutex_protocol: MutexProtocol::PrioNone,
+            mutex_ceiling: sync::DEFAULT_CEILING,
+            functions: Function::iter()
+                .filter(|&func| func != Function::Spawn)
+                .collect(),
+            symmetry_reduction: false,
+            relative_priorities: false,
+        }
+    }
+
+    // The limits of the modeled system, e.g. the thread limit and the priority ranges
+    pub fn config(mut self, config: ModelConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    // The largest TID tried as a caller, which is the thread limit unless it is given
+    pub fn max_tid(mut self, max_tid: u32) -> Self {
+        self.max_tid = Some(max_tid);
+        self
+    }
+
+    // The number of calls explored after the launch
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    // Restricts the priorities passed to the functions, e.g. to keep the tree small
+    pub fn priority_range(mut self, min: u32, max: u32) -> Self {
+        self.priority_range = Some((min, max));
+        self
+    }
+
+    // The protocol the mutexes are initialized with, e.g. to test priority inheritance
+    pub fn mutex_protocol(mut self, protocol: MutexProtocol) -> Self {
+        self.mutex_protocol = protocol;
+        self
+    }
+
+    // The priority ceiling the mutexes are initialized with for PTHREAD_PRIO_PROTECT
+    pub fn mutex_ceiling(mut self, ceiling: u32) -> Self {
+        self.mutex_ceiling = ceiling;
+        self
+    }
+
+    pub fn functions(mut self, functions: Vec<Function>) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    // Keeps only one representative of the states that differ just in the placement on cores
+    pub fn symmetry_reduction(mut self, enabled: bool) -> Self {
+        self.symmetry_reduction = enabled;
+        self
+    }
+
+    // Tries one priority per position relative to the priorities in the state, i.e. below all,
+    // equal to each, between each two and above all, instead of every priority
+    pub fn relative_priorities(mut self, enabled: bool) -> Self {
+        self.relative_priorities = enabled;
+        self
+    }
+
+    fn spawn(&self) -> (scheduler::State, Vec<Transition>) {
+        let initial_state = scheduler::State::new(self.num_core)
+            .with_config(self.config)
+            .with_mutex_protocol(self.mutex_protocol)
+            .with_mutex_ceiling(self.mutex_ceiling);
+        let transitions = get_function(Function::Spawn).call(&initial_state, 0, &[]);
+        (initial_state, self.reduce(transitions))
+    }
+
+    fn reduce(&self, transitions: Vec<Transition>) -> Vec<Transition> {
+        if !self.symmetry_reduction {
+            return transitions;
+        }
 
-fn search(num_core: u32) {
-    OracleTree::init(num_core);
-    let tree = ORACLE_TREE.lock();
-    let mut stack: Vec<&Node> = tree.get_init_nodes();
-    let mut path = vec![];
+        transitions
+            .into_iter()
+            .map(|Transition { ret, next }| Transition {
+                ret,
+                next: next.canonical(),
+            })
+            .unique()
+            .collect()
+    }
+
+    pub fn explore(&self) -> OracleTree {
+        let (initial_state, transitions) = self.spawn();
+        let mut tree = OracleTree::from_spawn(initial_state, transitions);
+        for node in tree.get_init_nodes_mut().into_iter() {
+            self.expand(node, self.max_depth);
+        }
+        tree
+    }
+
+    // Breadth-first exploration that expands every distinct state once
+    pub fn explore_graph(&self) -> StateGraph {
+        let (initial_state, transitions) = self.spawn();
+        let mut graph = StateGraph::from_spawn(initial_state, transitions);
+        let mut frontier: Vec<usize> = graph
+            .get_edges(StateGraph::ROOT)
+            .iter()
+            .flat_map(|edge| edge.targets.iter().copied())
+            .collect();
+
+        for _ in 0..self.max_depth {
+            let mut next_frontier = vec![];
+            for id in frontier.into_iter() {
+                for (func, caller, args, transitions) in self.invocations(graph.get_state(id)) {
+                    next_frontier.append(&mut graph.add_edges(id, func, caller, args, transitions));
+                }
+            }
+            frontier = next_frontier;
+        }
+        graph
+    }
 
-    while let Some(current) = stack.pop() {
-        path.push(current);
-        for func in Function::iter() {
+    fn expand(&self, current: &mut Node, depth: usize) {
+        if depth == 0 {
+            return;
+        }
+
+        for (func, caller, args, transitions) in self.invocations(current.get_state()) {
+            for mut edge in Edge::from_transitions(func, caller, args, transitions) {
+                for next in edge.node_group.iter_mut() {
+                    self.expand(next, depth - 1);
+                }
+                current.add_edge(edge);
+            }
+        }
+    }
+
+    // The values tried for each argument of the function on the state
+    fn arg_domains(&self, func: Function, state: &scheduler::State) -> Vec<Vec<u32>> {
+        let f = get_function(func);
+        f.params()
+            .iter()
+            .map(|param| {
+                let mut domain = param.domain(state);
+                if *param != Param::Priority {
+                    return domain;
+                }
+                if let Some((min, max)) = self.priority_range {
+                    domain.retain(|prio| (min..=max).contains(prio));
+                }
+                match self.relative_priorities {
+                    true => priority_classes(domain, &state.priorities()),
+                    false => domain,
+                }
+            })
+            .collect()
+    }
+
+    // Calls every invokable combination of function, caller and arguments on the state
+    fn invocations(
+        &self,
+        state: &scheduler::State,
+    ) -> Vec<(Function, u32, Vec<u32>, Vec<Transition>)> {
+        let mut invocations = vec![];
+        for &func in self.functions.iter() {
             let f = get_function(func);
-            for args in cartesian_product(f.args()).into_iter() {
-                for caller in 1_u32..=MAX_TID {
-                    if f.is_invokable(current.get_state(), caller, &args) {
-                        let mut node_group = vec![];
-                        for next in f.call(current.get_state(), caller, &args) {
-                            let node = Node::new(next);
-                            stack.push(&node);
-                            node_group.push(node);
-                        }
+            for args in cartesian_product(&self.arg_domains(func, state)).into_iter() {
+                for caller in 1_u32..=self.max_tid.unwrap_or(self.config.max_threads) {
+                    if f.is_invokable(state, caller, &args) {
+                        let transitions = self.reduce(f.call(state, caller, &args));
+                        invocations.push((func, caller, args.clone(), transitions));
                     }
                 }
             }
         }
+        invocations
     }
 }
 
-// e.g.) [(0,2),(1,1),(3,4)] |-> [[0, 1, 3], [0, 1, 4], [1, 1, 3], [1, 1, 4], [2, 1, 3], [2, 1, 4]]
-fn cartesian_product(ak: &[(u32, u32)]) -> Vec<Vec<u32>> {
-    if ak.is_empty() {
-        vec![vec![]]
-    } else if ak.len() == 1 {
-        (ak[0].0..=ak[0].1).map(|x| vec![x]).collect()
-    } else {
-        let mut nxt_ak: Vec<(u32, u32)> = ak.to_vec();
-        let (fst, snd) = nxt_ak.pop().unwrap();
-        iproduct!(cartesian_product(&nxt_ak), fst..=snd)
-            .map(|(mut a, b)| {
-                a.append(&mut vec![b]);
+// e.g.) [[0,1,2],[1],[3,4]] |-> [[0, 1, 3], [0, 1, 4], [1, 1, 3], [1, 1, 4], [2, 1, 3], [2, 1, 4]]
+fn cartesian_product(domains: &[Vec<u32>]) -> Vec<Vec<u32>> {
+    match domains.split_last() {
+        None => vec![vec![]],
+        Some((last, rest)) => iproduct!(cartesian_product(rest), last.iter())
+            .map(|(mut a, &b)| {
+                a.push(b);
                 a
             })
-            .collect()
+            .collect(),
+    }
+}
+
+// The smallest priority of the domain in each class relative to the sorted priorities, so that
+// the representatives stay legal priorities
+// e.g.) ([1..=99], [3, 5, 6]) |-> [1, 3, 4, 5, 6, 7]
+fn priority_classes(domain: Vec<u32>, priorities: &[u32]) -> Vec<u32> {
+    domain
+        .into_iter()
+        .unique_by(|prio| priorities.binary_search(prio))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cartesian_product, priority_classes, Explorer};
+    use crate::errno;
+    use crate::spec::{config::ModelConfig, function::Function, sched_data::ALL_CORES};
+    use itertools::Itertools;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_cartesian_product() {
+        assert_eq!(cartesian_product(&[]), vec![Vec::<u32>::new()]);
+        assert_eq!(
+            cartesian_product(&[vec![0, 1, 2], vec![1], vec![3, 4]]),
+            vec![
+                vec![0, 1, 3],
+                vec![0, 1, 4],
+                vec![1, 1, 3],
+                vec![1, 1, 4],
+                vec![2, 1, 3],
+                vec![2, 1, 4],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_priority_classes() {
+        assert_eq!(
+            priority_classes((1..=99).collect(), &[3, 5, 6]),
+            vec![1, 3, 4, 5, 6, 7]
+        );
+        assert_eq!(
+            priority_classes((4..=10).collect(), &[3, 5, 6]),
+            vec![4, 5, 6, 7]
+        );
+        assert_eq!(
+            priority_classes((1..=99).collect(), &[1, 99]),
+            vec![1, 2, 99]
+        );
+        assert_eq!(priority_classes((1..=99).collect(), &[]), vec![1]);
+    }
+
+    #[test]
+    fn test_search_depth_zero() {
+        let tree = Explorer::new(2).max_depth(0).explore();
+        assert_eq!(tree.count_nodes(), 2);
+        assert!(tree
+            .get_init_nodes()
+            .iter()
+            .all(|node| node.get_edges().is_empty()));
+    }
+
+    #[test]
+    fn test_search() {
+        let tree = Explorer::new(1).max_depth(1).priority_range(1, 3).explore();
+
+        // 3 priorities, 2 policies, 2 detach states and 2 affinity masks for pthread_create,
+        // 2 masks for pthread_setaffinity, 3 priorities and 2 policies for
+        // pthread_setschedparam, 3 priorities for pthread_setschedprio and
+        // pthread_mutex_setprioceiling plus exit, join, lock, trylock, unlock, yield and the three
+        // functions on each of the condition variable and the semaphore. The time slice of task 1
+        // never expires as it is a SCHED_FIFO task
+        let num_paths = 3 * 2 * 2 * 2 + 2 + 3 * 2 + 3 * 2 + 6 + 3 + 3;
+        assert_eq!(tree.count_nodes(), 1 + num_paths);
+
+        let paths = tree.paths();
+        assert_eq!(paths.len(), num_paths);
+        for path in paths.iter() {
+            assert_eq!(path.len(), 2);
+            assert_eq!(path[0].fn_type, Function::Spawn);
+            assert_eq!(path[1].caller, 1);
+            assert!(path[1].group.contains(&path[1].state));
+            let expected_ret = match path[1].fn_type {
+                Function::PthreadJoin => errno::EDEADLK,
+                Function::PthreadMutexUnlock | Function::PthreadCondWait => errno::EPERM,
+                Function::SemTrywait => errno::EAGAIN,
+                // The mutex has no priority ceiling
+                Function::PthreadMutexSetprioceiling => errno::EINVAL,
+                // The empty mask names no core, whereas pthread_create takes it as inherited
+                Function::PthreadSetaffinity if path[1].args[1] == 0 => errno::EINVAL,
+                _ => errno::EOK,
+            };
+            assert_eq!(path[1].ret, expected_ret);
+        }
+
+        let dot = tree.to_dot();
+        assert!(dot.starts_with("digraph"));
+        assert_eq!(dot.matches("->").count(), tree.count_nodes());
+    }
+
+    // Slow, as every priority is tried
+    #[test]
+    #[ignore]
+    fn test_search_full_domain() {
+        let tree = Explorer::new(1).max_depth(1).explore();
+        let num_paths = 99 * 2 * 2 * 2 + 2 + 99 * 2 + 99 * 2 + 6 + 3 + 3;
+        assert_eq!(tree.count_nodes(), 1 + num_paths);
+    }
+
+    #[test]
+    fn test_explorer_config() {
+        // Only task 1 exists after the launch
+        let tree = Explorer::new(1)
+            .max_tid(0)
+            .functions(vec![Function::PthreadExit])
+            .explore();
+        assert_eq!(tree.count_nodes(), 1);
+
+        let tree = Explorer::new(1)
+            .max_depth(2)
+            .functions(vec![
+                Function::PthreadMutexLock,
+                Function::PthreadMutexUnlock,
+            ])
+            .explore();
+        // lock -> {lock (EDEADLK), unlock}, unlock (EPERM) -> {lock, unlock (EPERM)}
+        assert_eq!(tree.count_nodes(), 1 + 2 + 4);
+
+        let tree = Explorer::new(1)
+            .priority_range(2, 4)
+            .functions(vec![Function::PthreadCreate])
+            .explore();
+        let mut prios: Vec<u32> = tree.paths().iter().map(|path| path[1].args[0]).collect();
+        assert_eq!(prios.len(), 3 * 2 * 2 * 2);
+        prios.dedup();
+        assert_eq!(prios, vec![2, 3, 4]);
+
+        // The main thread is the only thread allowed, so every creation fails
+        let tree = Explorer::new(1)
+            .config(ModelConfig {
+                max_threads: 1,
+                fifo_priority_range: (1, 2),
+                rr_priority_range: (1, 2),
+                ..ModelConfig::default()
+            })
+            .functions(vec![Function::PthreadCreate])
+            .explore();
+        let paths = tree.paths();
+        assert_eq!(paths.len(), 2 * 2 * 2 * 2);
+        assert!(paths
+            .iter()
+            .all(|path| path[1].ret == errno::EAGAIN || path[1].ret == errno::EINVAL));
+    }
+
+    #[test]
+    fn test_relative_priorities() {
+        let explorer = Explorer::new(1).functions(vec![Function::PthreadSetschedprio]);
+        let tree = explorer.clone().relative_priorities(true).explore();
+        // Below task 1 is impossible, so only equal to and above it
+        let prios: Vec<u32> = tree.paths().iter().map(|path| path[1].args[1]).collect();
+        assert_eq!(prios, vec![1, 2]);
+
+        // Next to tasks of the priorities 1 and 2, the classes are 1, 2 and above 2
+        let tree = explorer
+            .clone()
+            .relative_priorities(true)
+            .max_depth(2)
+            .functions(vec![Function::PthreadCreate])
+            .explore();
+        let full = explorer
+            .max_depth(2)
+            .functions(vec![Function::PthreadCreate])
+            .priority_range(1, 4)
+            .explore();
+        assert!(tree.count_nodes() < full.count_nodes());
+        let second: Vec<u32> = tree
+            .paths()
+            .iter()
+            .filter(|path| path[1].args == [2, 1, 0, 0])
+            .map(|path| path[2].args[0])
+            .unique()
+            .collect();
+        assert_eq!(second, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_parallel_explorers() {
+        let handles: Vec<_> = (1..=3)
+            .map(|num_core| {
+                std::thread::spawn(move || {
+                    Explorer::new(num_core)
+                        .functions(vec![Function::PthreadExit])
+                        .explore()
+                })
+            })
+            .collect();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            let tree = handle.join().unwrap();
+            let num_core = i + 1;
+            // One spawn node per core, each followed by the exit of task 1
+            assert_eq!(tree.count_nodes(), 2 * num_core);
+        }
+    }
+
+    #[test]
+    fn test_explore_graph() {
+        let explorer = Explorer::new(1).max_depth(3).functions(vec![
+            Function::PthreadMutexLock,
+            Function::PthreadMutexUnlock,
+        ]);
+        let graph = explorer.explore_graph();
+
+        // The initial state, and task 1 running with the mutex unlocked or locked
+        assert_eq!(graph.num_states(), 3);
+        // spawn, lock (EOK/EDEADLK), unlock (EOK/EPERM)
+        assert_eq!(graph.num_edges(), 5);
+
+        let tree = explorer.explore();
+        assert!(tree.count_nodes() > graph.num_states());
+        let mut states_in_tree = HashSet::new();
+        tree.walk(&mut |path| {
+            states_in_tree.insert(path[path.len() - 1].1.get_state().clone());
+        });
+        assert_eq!(states_in_tree.len(), graph.num_states() - 1);
+
+        for (id, path) in (1..graph.num_states()).zip(graph.paths()) {
+            assert_eq!(path[0].fn_type, Function::Spawn);
+            assert_eq!(&path[path.len() - 1].state, graph.get_state(id));
+            for step in path.iter() {
+                assert!(step.group.contains(&step.state));
+            }
+        }
+    }
+
+    #[test]
+    fn test_symmetry_reduction() {
+        let explorer = Explorer::new(3)
+            .max_depth(1)
+            .functions(vec![Function::PthreadCreate, Function::PthreadExit]);
+        let graph = explorer.explore_graph();
+        let reduced = explorer.clone().symmetry_reduction(true).explore_graph();
+        assert!(reduced.num_states() < graph.num_states());
+
+        // Every state has its representative in the reduced graph, unless a task is restricted to
+        // some of the cores and the cores are no longer interchangeable
+        for id in 1..graph.num_states() {
+            let state = graph.get_state(id).canonical();
+            if state.tasks().any(|task| task.affinity != ALL_CORES) {
+                continue;
+            }
+            assert!(reduced.get_id(&state).is_some(), "{:?}", state);
+        }
+        for id in 1..reduced.num_states() {
+            let state = reduced.get_state(id);
+            assert_eq!(&state.canonical(), state);
+        }
+
+        let tree = explorer.symmetry_reduction(true).explore();
+        assert_eq!(tree.get_init_nodes().len(), 1);
     }
 }
@@ -1,4 +1,7 @@
+pub mod config;
 pub mod cpu;
 pub mod function;
+pub mod notation;
 pub mod sched_data;
 pub mod scheduler;
+pub mod sync;
@@ -0,0 +1,74 @@
+use crate::spec::sched_data::Policy;
+
+// The limits of the modeled system, which have to match those of the machine the tests run on
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ModelConfig {
+    // The number of threads that may be created in a run including the main thread, as TIDs are
+    // never reused
+    pub max_threads: u32,
+    // sched_get_priority_min and sched_get_priority_max of SCHED_FIFO and SCHED_RR
+    pub fifo_priority_range: (u32, u32),
+    pub rr_priority_range: (u32, u32),
+    // The scheduling parameters of the main thread
+    pub initial_priority: u32,
+    pub initial_policy: Policy,
+}
+
+impl Default for ModelConfig {
+    fn default() -> Self {
+        ModelConfig {
+            max_threads: 4,
+            fifo_priority_range: (1, 99),
+            rr_priority_range: (1, 99),
+            initial_priority: 1,
+            initial_policy: Policy::Fifo,
+        }
+    }
+}
+
+impl ModelConfig {
+    pub fn priority_range(&self, policy: Policy) -> (u32, u32) {
+        match policy {
+            Policy::Fifo => self.fifo_priority_range,
+            Policy::RoundRobin => self.rr_priority_range,
+        }
+    }
+
+    pub fn is_valid_priority(&self, policy: Policy, prio: u32) -> bool {
+        let (min, max) = self.priority_range(policy);
+        min <= prio && prio <= max
+    }
+
+    // Every priority of any of the policies
+    pub fn priorities(&self) -> Vec<u32> {
+        let (fifo_min, fifo_max) = self.fifo_priority_range;
+        let (rr_min, rr_max) = self.rr_priority_range;
+        (fifo_min.min(rr_min)..=fifo_max.max(rr_max))
+            .filter(|&prio| {
+                self.is_valid_priority(Policy::Fifo, prio)
+                    || self.is_valid_priority(Policy::RoundRobin, prio)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ModelConfig;
+    use crate::spec::sched_data::Policy;
+
+    #[test]
+    fn test_priorities() {
+        let config = ModelConfig::default();
+        assert_eq!(config.priorities().len(), 99);
+
+        let config = ModelConfig {
+            fifo_priority_range: (1, 3),
+            rr_priority_range: (5, 6),
+            ..ModelConfig::default()
+        };
+        assert_eq!(config.priorities(), vec![1, 2, 3, 5, 6]);
+        assert!(config.is_valid_priority(Policy::Fifo, 3));
+        assert!(!config.is_valid_priority(Policy::RoundRobin, 3));
+    }
+}
@@ -1,23 +1,23 @@
 #![allow(clippy::upper_case_acronyms)]
 use crate::spec::sched_data;
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+// Affinity masks are 32-bit, so they can name at most this many cores
+pub const MAX_CORES: u32 = 32;
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub(crate) struct CPU {
     pub(crate) cores: Vec<Core>,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub(crate) struct Core {
     pub(crate) id: u32,
     pub(crate) task: Option<sched_data::TaskControlBlock>,
 }
 
 impl CPU {
-    pub(crate) const fn const_new() -> Self {
-        CPU { cores: Vec::new() }
-    }
-
     pub(crate) fn new(n: u32) -> Self {
+        assert!(n <= MAX_CORES, "an affinity mask cannot name {} cores", n);
         let mut cores = vec![];
         for id in 0..n {
             cores.push(Core { id, task: None });
@@ -25,6 +25,11 @@ impl CPU {
         CPU { cores }
     }
 
+    // The affinity mask that contains every core
+    pub(crate) fn mask(&self) -> u32 {
+        self.cores.iter().fold(0, |mask, core| mask | 1 << core.id)
+    }
+
     pub(crate) fn get_idle_cores(&self) -> Vec<&Core> {
         self.cores
             .iter()
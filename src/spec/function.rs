@@ -1,40 +1,207 @@
+mod pthread_cond_broadcast;
+mod pthread_cond_signal;
+mod pthread_cond_wait;
 mod pthread_create;
 mod pthread_exit;
+mod pthread_join;
+mod pthread_mutex_lock;
+mod pthread_mutex_setprioceiling;
+mod pthread_mutex_trylock;
+mod pthread_mutex_unlock;
+mod pthread_setaffinity;
+mod pthread_setschedparam;
+mod pthread_setschedprio;
+mod quantum_expiry;
+mod sched_yield;
+mod sem_post;
+mod sem_trywait;
+mod sem_wait;
 mod spawn;
-use crate::spec::scheduler;
-use strum_macros::EnumIter;
+use crate::{
+    errno,
+    spec::{sched_data::Policy, scheduler, sync},
+};
+use itertools::Itertools;
+use strum_macros::{Display, EnumIter, EnumString};
+
+// The value returned by the function (EOK or an errno) and the resulting state
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Transition {
+    pub ret: usize,
+    pub next: scheduler::State,
+}
 
 pub trait Formalized {
     fn is_invokable(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> bool;
 
-    fn args(&self) -> &[(u32, u32)];
+    // The kind of each argument
+    fn params(&self) -> &[Param];
 
-    fn call(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> Vec<scheduler::State>;
+    fn call(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> Vec<Transition>;
 }
 
-fn check_args(f: &dyn Formalized, args: &[u32]) -> bool {
-    assert_eq!(args.len(), f.args().len());
+// The kind of value an argument takes. Its domain may depend on the current state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Param {
+    // A scheduling priority of SCHED_FIFO or SCHED_RR
+    Priority,
+    // SCHED_FIFO or SCHED_RR
+    Policy,
+    // 0 or 1
+    Bool,
+    // A mask of cores. Only those of the CPU are tried, but others are accepted and ignored
+    Affinity,
+    // The TID of a task that has been created
+    Tid,
+    // The ID of a synchronization object of the kind
+    ObjectId(sync::Object),
+}
 
-    for (i, &arg) in args.iter().enumerate() {
-        let (min, max) = f.args()[i];
-        if !(min <= arg && arg <= max) {
-            return false;
+impl Param {
+    // Every value the argument may take on the state, in ascending order
+    pub fn domain(&self, current: &scheduler::State) -> Vec<u32> {
+        match self {
+            Param::Priority => current.config.priorities(),
+            Param::Policy => vec![Policy::Fifo as u32, Policy::RoundRobin as u32],
+            Param::Bool => vec![0, 1],
+            Param::Affinity => (0..=current.cpu.mask()).collect(),
+            Param::Tid => current.tasks().map(|task| task.tid).sorted().collect(),
+            Param::ObjectId(object) => current.object_ids(*object),
         }
     }
-    true
+
+    fn accepts(&self, current: &scheduler::State, arg: u32) -> bool {
+        match self {
+            Param::Affinity => true,
+            _ => self.domain(current).contains(&arg),
+        }
+    }
+}
+
+fn success(states: Vec<scheduler::State>) -> Vec<Transition> {
+    states
+        .into_iter()
+        .map(|next| Transition {
+            ret: errno::EOK,
+            next,
+        })
+        .collect()
+}
+
+// A failed call returns an errno and leaves the state untouched
+fn failure(current: &scheduler::State, ret: usize) -> Vec<Transition> {
+    vec![Transition {
+        ret,
+        next: current.clone(),
+    }]
+}
+
+fn check_args(f: &dyn Formalized, current: &scheduler::State, args: &[u32]) -> bool {
+    assert_eq!(args.len(), f.params().len());
+
+    f.params()
+        .iter()
+        .zip(args.iter())
+        .all(|(param, &arg)| param.accepts(current, arg))
 }
 
-#[derive(Debug, PartialEq, Eq, EnumIter)]
+// Debug gives the names used by the harness, Display and FromStr the C names used in scenarios
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter, EnumString, Display)]
+#[strum(serialize_all = "snake_case")]
 pub enum Function {
+    PthreadCondBroadcast,
+    PthreadCondSignal,
+    PthreadCondWait,
     PthreadCreate,
     PthreadExit,
+    PthreadJoin,
+    PthreadMutexLock,
+    PthreadMutexSetprioceiling,
+    PthreadMutexTrylock,
+    PthreadMutexUnlock,
+    PthreadSetaffinity,
+    PthreadSetschedparam,
+    PthreadSetschedprio,
+    QuantumExpiry,
+    SchedYield,
+    SemPost,
+    SemTrywait,
+    SemWait,
     Spawn,
 }
 
 pub fn get_function(fn_type: Function) -> &'static dyn Formalized {
     match fn_type {
         Function::Spawn => &spawn::FUNCTION,
+        Function::PthreadCondBroadcast => &pthread_cond_broadcast::FUNCTION,
+        Function::PthreadCondSignal => &pthread_cond_signal::FUNCTION,
+        Function::PthreadCondWait => &pthread_cond_wait::FUNCTION,
         Function::PthreadCreate => &pthread_create::FUNCTION,
         Function::PthreadExit => &pthread_exit::FUNCTION,
+        Function::PthreadJoin => &pthread_join::FUNCTION,
+        Function::PthreadMutexLock => &pthread_mutex_lock::FUNCTION,
+        Function::PthreadMutexSetprioceiling => &pthread_mutex_setprioceiling::FUNCTION,
+        Function::PthreadMutexTrylock => &pthread_mutex_trylock::FUNCTION,
+        Function::PthreadMutexUnlock => &pthread_mutex_unlock::FUNCTION,
+        Function::PthreadSetaffinity => &pthread_setaffinity::FUNCTION,
+        Function::PthreadSetschedparam => &pthread_setschedparam::FUNCTION,
+        Function::PthreadSetschedprio => &pthread_setschedprio::FUNCTION,
+        Function::QuantumExpiry => &quantum_expiry::FUNCTION,
+        Function::SchedYield => &sched_yield::FUNCTION,
+        Function::SemPost => &sem_post::FUNCTION,
+        Function::SemTrywait => &sem_trywait::FUNCTION,
+        Function::SemWait => &sem_wait::FUNCTION,
+    }
+}
+
+// Calls the function on a state written in the notation and returns every errno with the next
+// state in the notation
+#[cfg(test)]
+fn call(fn_type: Function, state: &str, caller: u32, args: &[u32]) -> Vec<(usize, String)> {
+    let state: scheduler::State = state.parse().unwrap();
+    get_function(fn_type)
+        .call(&state, caller, args)
+        .into_iter()
+        .map(|t| (t.ret, t.next.to_string()))
+        .collect()
+}
+
+// The result of a successful call that leads to a single state
+#[cfg(test)]
+fn ok(state: &str) -> Vec<(usize, String)> {
+    vec![(errno::EOK, state.to_string())]
+}
+
+// Checks that each call by the caller fails with the errno and leaves the state as it is
+#[cfg(test)]
+fn assert_errors(fn_type: Function, caller: u32, cases: &[(&str, &[u32], usize)]) {
+    for &(state, args, ret) in cases {
+        assert_eq!(
+            call(fn_type, state, caller, args),
+            vec![(ret, state.to_string())],
+            "{} {:?}",
+            state,
+            args
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Param;
+    use crate::spec::{scheduler::State, sync::Object};
+
+    #[test]
+    fn test_param_domain() {
+        let state: State = "C0:T1/p2 RQ:[T3/p1] X:[T2/p1] M0:T1[]".parse().unwrap();
+        assert_eq!(Param::Tid.domain(&state), vec![1, 2, 3]);
+        assert_eq!(Param::ObjectId(Object::Mutex).domain(&state), vec![0]);
+        assert_eq!(Param::Policy.domain(&state), vec![1, 2]);
+        assert_eq!(Param::Affinity.domain(&state), vec![0, 1]);
+        assert_eq!(Param::Affinity.domain(&State::new(3)).len(), 8);
+
+        let priorities = Param::Priority.domain(&State::new(1));
+        assert_eq!((priorities[0], priorities[priorities.len() - 1]), (1, 99));
+        assert!(Param::Tid.domain(&State::new(1)).is_empty());
     }
 }
@@ -0,0 +1,170 @@
+use itertools::Itertools;
+
+use super::Param;
+use crate::{
+    errno,
+    spec::{sched_data::TaskState, scheduler, sync::Object},
+};
+
+pub struct PthreadCondBroadcast;
+
+impl super::Formalized for PthreadCondBroadcast {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, _args: &[u32]) -> bool {
+        current.is_running(caller)
+    }
+
+    // Condition variable ID
+    fn params(&self) -> &[Param] {
+        &[Param::ObjectId(Object::Cond)]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        let mut next = current.clone();
+        let cond = next.get_cond_mut(args[0]).unwrap();
+        let mutex = match cond.mutex.take() {
+            Some(mutex) => mutex,
+            None => return super::success(vec![next]),
+        };
+        let waiters: Vec<_> = cond.wait_queue.0.drain(..).collect();
+
+        // The waiters line up for a mutex that is held, e.g. by the caller
+        if next.get_mutex(mutex).unwrap().owner.is_some() {
+            for waiter in waiters.into_iter() {
+                next.reacquire_mutex(mutex, waiter);
+            }
+            return super::success(next.schedule());
+        }
+
+        // Otherwise every waiter becomes ready and the first one to run takes the free mutex, so
+        // any of those that are dispatched at once may win. The others block on it again
+        let tids: Vec<u32> = waiters.iter().map(|waiter| waiter.tid).collect();
+        for mut waiter in waiters.into_iter() {
+            waiter.state = TaskState::Ready;
+            next.ready_queue.enqueue_tail(waiter);
+        }
+        let states = next
+            .schedule()
+            .into_iter()
+            .flat_map(|state| {
+                let mut winners: Vec<u32> = tids
+                    .iter()
+                    .copied()
+                    .filter(|&tid| state.is_running(tid))
+                    .collect();
+                if winners.is_empty() {
+                    winners = state
+                        .ready_queue
+                        .iter()
+                        .map(|task| task.tid)
+                        .filter(|tid| tids.contains(tid))
+                        .take(1)
+                        .collect();
+                }
+                winners
+                    .into_iter()
+                    .flat_map(|winner| {
+                        let mut next = state.clone();
+                        next.get_mutex_mut(mutex).unwrap().owner = Some(winner);
+                        for &loser in tids.iter().filter(|&&tid| tid != winner) {
+                            let task = match next.ready_queue.remove(loser) {
+                                Some(task) => task,
+                                None => next.block(loser),
+                            };
+                            next.reacquire_mutex(mutex, task);
+                        }
+                        next.update_priority(winner);
+                        next.schedule()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unique()
+            .collect();
+        super::success(states)
+    }
+}
+
+pub static FUNCTION: PthreadCondBroadcast = PthreadCondBroadcast;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::function::{call, Function};
+
+    #[test]
+    fn test_pthread_cond_broadcast() {
+        let ok = |states: &[&str]| {
+            states
+                .iter()
+                .map(|state| (errno::EOK, state.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            call(
+                Function::PthreadCondBroadcast,
+                "C0:T1/p1 RQ:[] X:[] M0:-",
+                1,
+                &[0]
+            ),
+            ok(&["C0:T1/p1 RQ:[] X:[] M0:-"])
+        );
+
+        // Every waiter moves to the mutex held by the caller
+        assert_eq!(
+            call(
+                Function::PthreadCondBroadcast,
+                "C0:T1/p2 RQ:[] X:[] M0:T1 CV0:M0[T2/p3,T3/p1]",
+                1,
+                &[0]
+            ),
+            ok(&["C0:T1/p2 RQ:[] X:[] M0:T1[T2/p3,T3/p1]"])
+        );
+
+        // On one core the highest-priority waiter runs first and wins the free mutex
+        assert_eq!(
+            call(
+                Function::PthreadCondBroadcast,
+                "C0:T1/p2 RQ:[] X:[] M0:- CV0:M0[T2/p3,T3/p3]",
+                1,
+                &[0]
+            ),
+            ok(&["C0:T2/p3 RQ:[T1/p2] X:[] M0:T2[T3/p3]"])
+        );
+        // With idle cores, either of the dispatched waiters may win
+        assert_eq!(
+            call(
+                Function::PthreadCondBroadcast,
+                "C0:T1/p2 C1:- C2:- RQ:[] X:[] M0:- CV0:M0[T2/p3,T3/p1]",
+                1,
+                &[0]
+            ),
+            ok(&[
+                "C0:T1/p2 C1:T2/p3 C2:- RQ:[] X:[] M0:T2[T3/p1]",
+                "C0:T1/p2 C1:- C2:T3/p1 RQ:[] X:[] M0:T3[T2/p3]",
+                "C0:T1/p2 C1:- C2:T2/p3 RQ:[] X:[] M0:T2[T3/p1]",
+                "C0:T1/p2 C1:T3/p1 C2:- RQ:[] X:[] M0:T3[T2/p3]",
+            ])
+        );
+        // A waiter that does not get a core only takes the mutex if none of the others does
+        assert_eq!(
+            call(
+                Function::PthreadCondBroadcast,
+                "C0:T1/p4 RQ:[] X:[] M0:- CV0:M0[T2/p3,T3/p1]",
+                1,
+                &[0]
+            ),
+            ok(&["C0:T1/p4 RQ:[T2/p3] X:[] M0:T2[T3/p1]"])
+        );
+    }
+}
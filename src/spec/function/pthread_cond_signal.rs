@@ -0,0 +1,93 @@
+use super::Param;
+use crate::{
+    errno,
+    spec::{scheduler, sync::Object},
+};
+
+pub struct PthreadCondSignal;
+
+impl super::Formalized for PthreadCondSignal {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, _args: &[u32]) -> bool {
+        current.is_running(caller)
+    }
+
+    // Condition variable ID
+    fn params(&self) -> &[Param] {
+        &[Param::ObjectId(Object::Cond)]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        // The highest-priority waiter, if any, wakes up and takes the mutex back
+        let mut next = current.clone();
+        let cond = next.get_cond_mut(args[0]).unwrap();
+        let (mutex, waiter) = match (cond.mutex, cond.wait_queue.dequeue()) {
+            (Some(mutex), Some(waiter)) => (mutex, waiter),
+            _ => return super::success(vec![next]),
+        };
+        if cond.wait_queue.0.is_empty() {
+            cond.mutex = None;
+        }
+        next.reacquire_mutex(mutex, waiter);
+        super::success(next.schedule())
+    }
+}
+
+pub static FUNCTION: PthreadCondSignal = PthreadCondSignal;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::function::{assert_errors, call, ok, Function};
+
+    #[test]
+    fn test_pthread_cond_signal() {
+        // Nothing happens without waiters
+        assert_eq!(
+            call(
+                Function::PthreadCondSignal,
+                "C0:T1/p1 RQ:[] X:[] M0:-",
+                1,
+                &[0]
+            ),
+            ok("C0:T1/p1 RQ:[] X:[] M0:-")
+        );
+
+        // The waiter of the highest priority takes the free mutex and preempts the caller
+        assert_eq!(
+            call(
+                Function::PthreadCondSignal,
+                "C0:T1/p2 RQ:[] X:[] M0:- CV0:M0[T2/p3,T3/p1]",
+                1,
+                &[0]
+            ),
+            ok("C0:T2/p3 RQ:[T1/p2] X:[] M0:T2 CV0:M0[T3/p1]")
+        );
+        // or waits for the mutex the caller holds, lending it its priority
+        assert_eq!(
+            call(
+                Function::PthreadCondSignal,
+                "C0:T1/p2 RQ:[] X:[] M0i:T1 CV0:M0[T2/p3]",
+                1,
+                &[0]
+            ),
+            ok("C0:T1/p3/b2 RQ:[] X:[] M0i:T1[T2/p3]")
+        );
+
+        assert_errors(
+            Function::PthreadCondSignal,
+            1,
+            &[("C0:T1/p1 RQ:[] X:[] M0:-", &[1], errno::EINVAL)],
+        );
+    }
+}
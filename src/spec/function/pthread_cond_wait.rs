@@ -0,0 +1,116 @@
+use super::Param;
+use crate::{
+    errno,
+    spec::{
+        scheduler,
+        sync::{MutexKind, Object},
+    },
+};
+
+pub struct PthreadCondWait;
+
+impl super::Formalized for PthreadCondWait {
+    // Waiting with a normal mutex that the caller does not own is undefined behavior
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> bool {
+        current.is_running(caller)
+            && match current.get_mutex(args[1]) {
+                Some(mutex) => mutex.kind != MutexKind::Normal || mutex.owner == Some(caller),
+                None => true,
+            }
+    }
+
+    // Condition variable ID, Mutex ID
+    fn params(&self) -> &[Param] {
+        &[
+            Param::ObjectId(Object::Cond),
+            Param::ObjectId(Object::Mutex),
+        ]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+        if current.get_mutex(args[1]).unwrap().owner != Some(caller) {
+            return super::failure(current, errno::EPERM);
+        }
+        // Concurrent waits on a condition variable have to use the same mutex
+        if matches!(current.get_cond(args[0]).unwrap().mutex, Some(mutex) if mutex != args[1]) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        // The caller sleeps on the condition variable and releases the mutex at once
+        let mut next = current.clone();
+        let task = next.block(caller);
+        let cond = next.get_cond_mut(args[0]).unwrap();
+        cond.mutex = Some(args[1]);
+        cond.wait_queue.enqueue(task);
+        next.release_mutex(args[1], caller);
+        super::success(next.schedule())
+    }
+}
+
+pub static FUNCTION: PthreadCondWait = PthreadCondWait;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::{
+        function::{assert_errors, call, get_function, ok, Function},
+        scheduler::State,
+    };
+
+    #[test]
+    fn test_pthread_cond_wait() {
+        // The mutex goes to its waiter, and the caller waits behind the other one of its priority
+        assert_eq!(
+            call(
+                Function::PthreadCondWait,
+                "C0:T1/p2 RQ:[T4/p1] X:[] M0:T1[T3/p1] CV0:M0[T2/p2]",
+                1,
+                &[0, 0]
+            ),
+            ok("C0:T4/p1 RQ:[T3/p1] X:[] M0:T3 CV0:M0[T2/p2,T1/p2]")
+        );
+        // The caller drops the priority it inherited through the mutex
+        assert_eq!(
+            call(
+                Function::PthreadCondWait,
+                "C0:T1/p3/b1 RQ:[] X:[] M0i:T1[T2/p3]",
+                1,
+                &[0, 0]
+            ),
+            ok("C0:T2/p3 RQ:[] X:[] M0i:T2 CV0:M0[T1/p1]")
+        );
+    }
+
+    #[test]
+    fn test_pthread_cond_wait_errors() {
+        assert_errors(
+            Function::PthreadCondWait,
+            1,
+            &[
+                ("C0:T1/p1 RQ:[] X:[] M0:-", &[0, 0], errno::EPERM),
+                ("C0:T1/p1 RQ:[] X:[] M0:T2", &[0, 0], errno::EPERM),
+                ("C0:T1/p1 RQ:[] X:[] M0:T1", &[1, 0], errno::EINVAL),
+                ("C0:T1/p1 RQ:[] X:[] M0:T1", &[0, 1], errno::EINVAL),
+                // The waiters released another mutex
+                (
+                    "C0:T1/p1 RQ:[] X:[] M0:T1 CV0:M1[T2/p1]",
+                    &[0, 0],
+                    errno::EINVAL,
+                ),
+            ],
+        );
+
+        let state: State = "C0:T1/p1 RQ:[] X:[] M0n:-".parse().unwrap();
+        assert!(!get_function(Function::PthreadCondWait).is_invokable(&state, 1, &[0, 0]));
+    }
+}
@@ -1,4 +1,8 @@
-use crate::spec::scheduler;
+use super::Param;
+use crate::{
+    errno,
+    spec::{sched_data::Policy, scheduler},
+};
 
 pub struct PthreadCreate;
 
@@ -15,19 +19,48 @@ impl super::Formalized for PthreadCreate {
         false
     }
 
-    // TODO check
-    // Priority
-    fn args(&self) -> &[(u32, u32)] {
-        &[(1, 99)]
+    // Priority, Policy (SCHED_FIFO or SCHED_RR),
+    // Detach state (PTHREAD_CREATE_JOINABLE or PTHREAD_CREATE_DETACHED),
+    // Affinity mask (0 inherits the affinity of the caller)
+    fn params(&self) -> &[Param] {
+        &[Param::Priority, Param::Policy, Param::Bool, Param::Affinity]
     }
 
-    fn call(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> Vec<scheduler::State> {
-        assert!(super::check_args(self, args));
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
         assert!(self.is_invokable(current, caller, args));
 
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
         let prio = args[0];
+        let policy = Policy::from_arg(args[1]).unwrap();
+        if !current.config.is_valid_priority(policy, prio) {
+            return super::failure(current, errno::EINVAL);
+        }
+        if current.next_tid() > current.config.max_threads {
+            return super::failure(current, errno::EAGAIN);
+        }
+
+        let affinity = match args[3] {
+            0 => current.get_running_task(caller).unwrap().affinity,
+            mask => match current.normalize_affinity(mask) {
+                Some(affinity) => affinity,
+                None => return super::failure(current, errno::EINVAL),
+            },
+        };
 
-        current.create_task(prio).schedule()
+        let detached = args[2] == 1;
+
+        super::success(
+            current
+                .create_task_with_attr(prio, policy, detached, affinity)
+                .schedule(),
+        )
     }
 }
 
@@ -35,55 +68,21 @@ pub static FUNCTION: PthreadCreate = PthreadCreate;
 
 #[cfg(test)]
 mod tests {
+    use crate::errno;
+    use crate::scenario::Scenario;
     use crate::spec::{
+        config::ModelConfig,
         function::{get_function, Function},
-        sched_data::{ReadyQueue, TaskControlBlock, TaskState},
         scheduler::State,
     };
-    use std::collections::VecDeque;
 
     #[test]
     fn test_pthread_create() {
-        let mut states = State::new(2).create_task(1).schedule();
-
-        let mut new_states = vec![];
-        for state in states.into_iter() {
-            for new_state in get_function(Function::PthreadCreate)
-                .call(&state, 1, &[3])
-                .into_iter()
-            {
-                if !new_states.contains(&new_state) {
-                    new_states.push(new_state);
-                }
-            }
-        }
-        states = new_states;
-
-        new_states = vec![];
-        for state in states.iter() {
-            for new_state in get_function(Function::PthreadCreate)
-                .call(&state, 1, &[2])
-                .into_iter()
-            {
-                if !new_states.contains(&new_state) {
-                    new_states.push(new_state);
-                }
-            }
-        }
-        states = new_states;
-
-        new_states = vec![];
-        for state in states.iter() {
-            for new_state in get_function(Function::PthreadCreate)
-                .call(&state, 2, &[4])
-                .into_iter()
-            {
-                if !new_states.contains(&new_state) {
-                    new_states.push(new_state);
-                }
-            }
-        }
-        states = new_states;
+        let states = include_str!("../../../scenarios/create_preempt.txt")
+            .parse::<Scenario>()
+            .unwrap()
+            .run(ModelConfig::default())
+            .unwrap();
 
         for state in states.into_iter() {
             let t0 = state.cpu.cores[0].task.as_ref().unwrap();
@@ -92,21 +91,67 @@ mod tests {
             assert!((t0.tid == 2 || t1.tid == 2) && (t0.tid == 4 || t1.tid == 4));
             assert!((t0.prio == 3 || t1.prio == 3) && (t0.prio == 4 || t1.prio == 4));
             assert_eq!(
-                state.ready_queue,
-                ReadyQueue(VecDeque::from(vec![
-                    TaskControlBlock {
-                        tid: 3,
-                        prio: 2,
-                        state: TaskState::Ready,
-                    },
-                    TaskControlBlock {
-                        tid: 1,
-                        prio: 1,
-                        state: TaskState::Ready,
-                    },
-                ])),
+                state
+                    .ready_queue
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>(),
+                vec!["T3/p2", "T1/p1"]
             );
             assert!(state.terminated_tasks.is_empty());
         }
     }
+
+    #[test]
+    fn test_pthread_create_errors() {
+        let create = get_function(Function::PthreadCreate);
+        let state = State::new(1).create_task(1).schedule().remove(0);
+
+        for args in [
+            [0, 1, 0, 0],
+            [100, 1, 0, 0],
+            [1, 0, 0, 0],
+            [1, 3, 0, 0],
+            [1, 1, 2, 0],
+            [1, 1, 0, 16],
+            // Core 1 does not exist
+            [1, 1, 0, 2],
+        ] {
+            let transitions = create.call(&state, 1, &args);
+            assert_eq!(transitions.len(), 1);
+            assert_eq!(transitions[0].ret, errno::EINVAL);
+            assert_eq!(transitions[0].next, state);
+        }
+
+        // The thread limit is reached when TID 4 exists
+        let mut state = state;
+        for _ in 2..=4 {
+            state = create.call(&state, 1, &[1, 1, 0, 0]).remove(0).next;
+        }
+        let transitions = create.call(&state, 1, &[1, 1, 0, 0]);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].ret, errno::EAGAIN);
+        assert_eq!(transitions[0].next, state);
+
+        // The priority has to be in the range of the policy
+        let config = ModelConfig {
+            max_threads: 1,
+            rr_priority_range: (1, 50),
+            ..ModelConfig::default()
+        };
+        let state = State::new(1)
+            .with_config(config)
+            .create_task(1)
+            .schedule()
+            .remove(0);
+        for (args, ret) in [
+            ([60, 2, 0, 0], errno::EINVAL),
+            ([60, 1, 0, 0], errno::EAGAIN),
+        ] {
+            let transitions = create.call(&state, 1, &args);
+            assert_eq!(transitions.len(), 1);
+            assert_eq!(transitions[0].ret, ret);
+            assert_eq!(transitions[0].next, state);
+        }
+    }
 }
@@ -1,4 +1,5 @@
-use crate::spec::{sched_data::TaskState, scheduler};
+use super::Param;
+use crate::spec::scheduler;
 
 pub struct PthreadExit;
 
@@ -15,32 +16,20 @@ impl super::Formalized for PthreadExit {
         false
     }
 
-    fn args(&self) -> &[(u32, u32)] {
+    fn params(&self) -> &[Param] {
         &[]
     }
 
-    fn call(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> Vec<scheduler::State> {
-        assert!(super::check_args(self, args));
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(super::check_args(self, current, args));
         assert!(self.is_invokable(current, caller, args));
 
-        let mut next = current.clone();
-        for (i, core) in current.cpu.cores.iter().enumerate() {
-            if let Some(task) = &core.task {
-                if task.tid == caller {
-                    let mut task = {
-                        let t = next.cpu.cores[i].task.take();
-                        assert!(t.is_some());
-                        t.unwrap()
-                    };
-
-                    task.state = TaskState::Terminated;
-                    next.terminated_tasks.push(task);
-                    return next.schedule();
-                }
-            }
-        }
-
-        unreachable!();
+        super::success(current.exit(caller).schedule())
     }
 }
 
@@ -48,50 +37,22 @@ pub(crate) static FUNCTION: PthreadExit = PthreadExit;
 
 #[cfg(test)]
 mod tests {
-    use crate::spec::{function, scheduler};
+    use crate::scenario::Scenario;
+    use crate::spec::{config::ModelConfig, sched_data::TaskState};
 
     #[test]
     fn test_pthread_exit() {
-        let mut states = scheduler::State::new(2).create_task(1).schedule();
-
-        let mut new_states = vec![];
-        for state in states.into_iter() {
-            for new_state in function::get_function(function::Function::PthreadCreate)
-                .call(&state, 1, &[3])
-                .into_iter()
-            {
-                if !new_states.contains(&new_state) {
-                    new_states.push(new_state);
-                }
-            }
-        }
-        states = new_states;
-
-        let mut new_states = vec![];
-        for state in states.into_iter() {
-            for new_state in function::get_function(function::Function::PthreadCreate)
-                .call(&state, 1, &[3])
-                .into_iter()
-            {
-                if !new_states.contains(&new_state) {
-                    new_states.push(new_state);
-                }
-            }
-        }
-        states = new_states;
-
-        let mut new_states = vec![];
-        for state in states.into_iter() {
-            for new_state in function::get_function(function::Function::PthreadExit)
-                .call(&state, 3, &[])
-                .into_iter()
-            {
-                if !new_states.contains(&new_state) {
-                    new_states.push(new_state);
-                }
-            }
+        let states = include_str!("../../../scenarios/create_and_exit.txt")
+            .parse::<Scenario>()
+            .unwrap()
+            .run(ModelConfig::default())
+            .unwrap();
+
+        for state in states.iter() {
+            assert_eq!(state.terminated_tasks.len(), 1);
+            assert_eq!(state.terminated_tasks[0].tid, 3);
+            assert_eq!(state.terminated_tasks[0].state, TaskState::Terminated);
+            assert!(!state.is_running(3));
         }
-        states = new_states;
-        println!("{:#?}", states);
     }
 }
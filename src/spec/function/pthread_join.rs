@@ -0,0 +1,143 @@
+use super::Param;
+use crate::{errno, spec::scheduler};
+
+pub struct PthreadJoin;
+
+impl super::Formalized for PthreadJoin {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> bool {
+        current.is_running(caller) && current.tasks().any(|task| task.tid == args[0])
+    }
+
+    // TID
+    fn params(&self) -> &[Param] {
+        &[Param::Tid]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(super::check_args(self, current, args));
+        assert!(self.is_invokable(current, caller, args));
+
+        let target = args[0];
+        // Joining itself or a thread that is joining the caller would never return
+        if target == caller
+            || current
+                .joiners
+                .iter()
+                .any(|(t, joiner)| *t == caller && joiner.tid == target)
+        {
+            return super::failure(current, errno::EDEADLK);
+        }
+
+        let task = current.tasks().find(|task| task.tid == target).unwrap();
+        // Only one thread may join a joinable thread
+        if task.detached || current.joiners.iter().any(|(t, _)| *t == target) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        let mut next = current.clone();
+        if current.is_terminated(target) {
+            // The zombie is reaped at once
+            for task in next.terminated_tasks.iter_mut() {
+                if task.tid == target {
+                    task.detached = true;
+                }
+            }
+            return super::success(vec![next]);
+        }
+
+        // The caller waits until the target exits
+        let joiner = next.block(caller);
+        next.joiners.push((target, joiner));
+        super::success(next.schedule())
+    }
+}
+
+pub static FUNCTION: PthreadJoin = PthreadJoin;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::scenario::Scenario;
+    use crate::spec::{
+        config::ModelConfig,
+        function::{assert_errors, call, ok, Function},
+    };
+
+    #[test]
+    fn test_pthread_join() {
+        // The caller blocks until the target exits, which reaps the target
+        let trace = "spawn; 1: pthread_create(1, 1, 0, 0); 1: pthread_join(2); 2: pthread_exit()"
+            .parse::<Scenario>()
+            .unwrap()
+            .trace(ModelConfig::default())
+            .unwrap();
+        assert_eq!(
+            trace[2]
+                .iter()
+                .map(|t| (t.ret, t.next.to_string()))
+                .collect::<Vec<_>>(),
+            ok("C0:T2/p1 RQ:[] X:[] M0:- J2:T1/p1")
+        );
+        assert_eq!(
+            trace[3][0].next.to_string(),
+            "C0:T1/p1 RQ:[] X:[T2/p1/d] M0:-"
+        );
+
+        // A thread that has not run yet can be joined too
+        let trace = include_str!("../../../scenarios/join_before_run.txt")
+            .parse::<Scenario>()
+            .unwrap()
+            .trace(ModelConfig::default())
+            .unwrap();
+        assert_eq!(
+            trace[3]
+                .iter()
+                .map(|t| (t.ret, t.next.to_string()))
+                .collect::<Vec<_>>(),
+            ok("C0:T2/p1 RQ:[] X:[] M0:- J2:T1/p5")
+        );
+        assert_eq!(
+            trace[4][0].next.to_string(),
+            "C0:T1/p5 RQ:[] X:[T2/p1/d] M0:-"
+        );
+
+        // A zombie is reaped without blocking
+        assert_eq!(
+            call(
+                Function::PthreadJoin,
+                "C0:T1/p1 RQ:[] X:[T2/p1] M0:-",
+                1,
+                &[2]
+            ),
+            ok("C0:T1/p1 RQ:[] X:[T2/p1/d] M0:-")
+        );
+    }
+
+    #[test]
+    fn test_pthread_join_errors() {
+        assert_errors(
+            Function::PthreadJoin,
+            1,
+            &[
+                ("C0:T1/p1 RQ:[] X:[] M0:-", &[1], errno::EDEADLK),
+                (
+                    "C0:T1/p1 RQ:[T2/p1] X:[] M0:- J1:T3/p1",
+                    &[3],
+                    errno::EDEADLK,
+                ),
+                ("C0:T1/p1 RQ:[T2/p1/d] X:[] M0:-", &[2], errno::EINVAL),
+                ("C0:T1/p1 RQ:[] X:[T2/p1/d] M0:-", &[2], errno::EINVAL),
+                (
+                    "C0:T1/p1 RQ:[T2/p1] X:[] M0:- J2:T3/p1",
+                    &[2],
+                    errno::EINVAL,
+                ),
+            ],
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use super::Param;
+use crate::{
+    errno,
+    spec::{
+        scheduler,
+        sync::{MutexKind, Object},
+    },
+};
+
+pub struct PthreadMutexLock;
+
+impl super::Formalized for PthreadMutexLock {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, _args: &[u32]) -> bool {
+        current.is_running(caller)
+    }
+
+    // Mutex ID
+    fn params(&self) -> &[Param] {
+        &[Param::ObjectId(Object::Mutex)]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        let mut next = current.clone();
+        let mutex = next.get_mutex(args[0]).unwrap();
+
+        if mutex.violates_ceiling(current.get_running_task(caller).unwrap().prio) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        // The owner of a priority-protection mutex runs at its ceiling
+        if mutex.owner.is_none() {
+            next.get_mutex_mut(args[0]).unwrap().owner = Some(caller);
+            next.update_priority(caller);
+            return super::success(vec![next]);
+        }
+
+        if mutex.owner == Some(caller) && mutex.kind == MutexKind::ErrorCheck {
+            return super::failure(current, errno::EDEADLK);
+        }
+
+        // The mutex is held (by the caller itself for a normal mutex), so the caller sleeps on it
+        // and lends its priority to the owner of a priority-inheritance mutex
+        let owner = mutex.owner.unwrap();
+        let task = next.block(caller);
+        next.get_mutex_mut(args[0])
+            .unwrap()
+            .wait_queue
+            .enqueue(task);
+        next.update_priority(owner);
+        super::success(next.schedule())
+    }
+}
+
+pub static FUNCTION: PthreadMutexLock = PthreadMutexLock;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::scenario::Scenario;
+    use crate::spec::{
+        config::ModelConfig,
+        function::{get_function, Function, Transition},
+        sched_data::TaskState,
+        scheduler::State,
+        sync::{Mutex, MutexKind},
+    };
+
+    #[test]
+    fn test_pthread_mutex_lock() {
+        let states = State::new(1).create_task(2).schedule();
+        assert_eq!(states.len(), 1);
+
+        let lock = get_function(Function::PthreadMutexLock);
+        let transitions = lock.call(&states[0], 1, &[0]);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].ret, errno::EOK);
+        let state = &transitions[0].next;
+        assert_eq!(state.mutexes[0].owner, Some(1));
+        assert!(state.is_running(1));
+
+        // The second task blocks on the mutex and the first one resumes
+        let transitions = get_function(Function::PthreadCreate).call(state, 1, &[3, 1, 0, 0]);
+        assert!(transitions[0].next.is_running(2));
+        let transitions = lock.call(&transitions[0].next, 2, &[0]);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].ret, errno::EOK);
+        let state = &transitions[0].next;
+        assert!(state.is_running(1));
+        let waiter = state.mutexes[0].wait_queue.iter().next().unwrap();
+        assert_eq!(waiter.tid, 2);
+        assert_eq!(waiter.state, TaskState::Waiting);
+    }
+
+    #[test]
+    fn test_pthread_mutex_relock() {
+        let lock = get_function(Function::PthreadMutexLock);
+        let state = State::new(1).create_task(1).schedule().remove(0);
+        let state = lock.call(&state, 1, &[0]).remove(0).next;
+
+        // An error-checking mutex reports the deadlock
+        assert_eq!(
+            lock.call(&state, 1, &[0]),
+            vec![Transition {
+                ret: errno::EDEADLK,
+                next: state.clone(),
+            }]
+        );
+
+        // A normal mutex deadlocks the caller
+        let mut state = state;
+        state.mutexes[0] = Mutex {
+            owner: Some(1),
+            ..Mutex::with_kind(0, MutexKind::Normal)
+        };
+        let transitions = lock.call(&state, 1, &[0]);
+        assert_eq!(transitions[0].ret, errno::EOK);
+        assert!(!transitions[0].next.is_running(1));
+
+        assert_eq!(lock.call(&state, 1, &[1])[0].ret, errno::EINVAL);
+    }
+
+    #[test]
+    fn test_pthread_mutex_lock_inherit() {
+        let states = "cores 1; protocol inherit; spawn; 1: pthread_mutex_lock(0)
+            1: pthread_create(3, 1, 0, 0); 2: pthread_mutex_lock(0)"
+            .parse::<Scenario>()
+            .unwrap()
+            .run(ModelConfig::default())
+            .unwrap();
+        assert_eq!(
+            states.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+            vec!["C0:T1/p3/b1 RQ:[] X:[] M0i:T1[T2/p3]"]
+        );
+
+        // Task 1 owns mutex 0 and waits for mutex 1, so the boost passes on to task 3
+        let state: State = "C0:T4/p5 RQ:[T3/p2/b1] X:[] M0i:T1[T2/p2] M1i:T3[T1/p2/b1]"
+            .parse()
+            .unwrap();
+        let transitions = get_function(Function::PthreadMutexLock).call(&state, 4, &[0]);
+        assert_eq!(
+            transitions
+                .iter()
+                .map(|t| (t.ret, t.next.to_string()))
+                .collect::<Vec<_>>(),
+            vec![(
+                errno::EOK,
+                "C0:T3/p5/b1 RQ:[] X:[] M0i:T1[T4/p5,T2/p2] M1i:T3[T1/p5/b1]".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_pthread_mutex_lock_protect() {
+        // The owner runs at the ceiling, so task 2 waits until the mutex is unlocked
+        let trace = "cores 1; protocol protect 5; spawn; 1: pthread_mutex_lock(0)
+            1: pthread_create(4, 1, 0, 0); 1: pthread_mutex_unlock(0)
+            2: pthread_mutex_lock(0); 2: pthread_create(6, 1, 0, 0); 3: pthread_mutex_lock(0)"
+            .parse::<Scenario>()
+            .unwrap()
+            .trace(ModelConfig::default())
+            .unwrap();
+        let states: Vec<String> = trace
+            .iter()
+            .map(|transitions| transitions[0].next.to_string())
+            .collect();
+        assert_eq!(
+            states[1..=4],
+            [
+                "C0:T1/p5/b1 RQ:[] X:[] M0p5:T1",
+                "C0:T1/p5/b1 RQ:[T2/p4] X:[] M0p5:T1",
+                "C0:T2/p4 RQ:[T1/p1] X:[] M0p5:-",
+                "C0:T2/p5/b4 RQ:[T1/p1] X:[] M0p5:T2",
+            ]
+        );
+
+        // A caller above the ceiling may not lock the mutex
+        let last = trace.last().unwrap();
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].ret, errno::EINVAL);
+        assert_eq!(
+            last[0].next.to_string(),
+            "C0:T3/p6 RQ:[T2/p5/b4,T1/p1] X:[] M0p5:T2"
+        );
+    }
+}
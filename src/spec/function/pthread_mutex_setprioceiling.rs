@@ -0,0 +1,116 @@
+use super::Param;
+use crate::{
+    errno,
+    spec::{
+        scheduler,
+        sync::{MutexProtocol, Object},
+    },
+};
+
+pub struct PthreadMutexSetprioceiling;
+
+impl super::Formalized for PthreadMutexSetprioceiling {
+    // The call locks the mutex for the change, so the caller would block while another thread
+    // holds it
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> bool {
+        current.is_running(caller)
+            && match current.get_mutex(args[0]) {
+                Some(mutex) => mutex.owner.is_none() || mutex.owner == Some(caller),
+                None => true,
+            }
+    }
+
+    // Mutex ID, Priority ceiling
+    fn params(&self) -> &[Param] {
+        &[Param::ObjectId(Object::Mutex), Param::Priority]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        let mut next = current.clone();
+        let mutex = next.get_mutex_mut(args[0]).unwrap();
+        if mutex.protocol != MutexProtocol::PrioProtect {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        // The caller that holds the mutex moves to the new ceiling, and may be preempted if
+        // it is lowered
+        mutex.ceiling = args[1];
+        next.update_priority(caller);
+        super::success(next.schedule())
+    }
+}
+
+pub static FUNCTION: PthreadMutexSetprioceiling = PthreadMutexSetprioceiling;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::{
+        function::{assert_errors, call, get_function, ok, Function},
+        scheduler::State,
+    };
+
+    #[test]
+    fn test_pthread_mutex_setprioceiling() {
+        assert_eq!(
+            call(
+                Function::PthreadMutexSetprioceiling,
+                "C0:T1/p1 RQ:[] X:[] M0p5:-",
+                1,
+                &[0, 3]
+            ),
+            ok("C0:T1/p1 RQ:[] X:[] M0p3:-")
+        );
+        // The owner follows the ceiling, and a lowered one gives way to a ready task
+        assert_eq!(
+            call(
+                Function::PthreadMutexSetprioceiling,
+                "C0:T1/p5/b1 RQ:[T2/p3] X:[] M0p5:T1",
+                1,
+                &[0, 7]
+            ),
+            ok("C0:T1/p7/b1 RQ:[T2/p3] X:[] M0p7:T1")
+        );
+        assert_eq!(
+            call(
+                Function::PthreadMutexSetprioceiling,
+                "C0:T1/p5/b1 RQ:[T2/p3] X:[] M0p5:T1",
+                1,
+                &[0, 2]
+            ),
+            ok("C0:T2/p3 RQ:[T1/p2/b1] X:[] M0p2:T1")
+        );
+
+        // The call would block on a mutex held by another thread
+        let state: State = "C0:T1/p1 RQ:[] X:[] M0p5:T2".parse().unwrap();
+        assert!(
+            !get_function(Function::PthreadMutexSetprioceiling).is_invokable(&state, 1, &[0, 3])
+        );
+    }
+
+    #[test]
+    fn test_pthread_mutex_setprioceiling_errors() {
+        assert_errors(
+            Function::PthreadMutexSetprioceiling,
+            1,
+            &[
+                ("C0:T1/p1 RQ:[] X:[] M0:-", &[0, 3], errno::EINVAL),
+                ("C0:T1/p1 RQ:[] X:[] M0i:-", &[0, 3], errno::EINVAL),
+                ("C0:T1/p1 RQ:[] X:[] M0p5:-", &[0, 0], errno::EINVAL),
+                ("C0:T1/p1 RQ:[] X:[] M0p5:-", &[0, 100], errno::EINVAL),
+                ("C0:T1/p1 RQ:[] X:[] M0p5:-", &[1, 3], errno::EINVAL),
+            ],
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use super::Param;
+use crate::{
+    errno,
+    spec::{scheduler, sync::Object},
+};
+
+pub struct PthreadMutexTrylock;
+
+impl super::Formalized for PthreadMutexTrylock {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, _args: &[u32]) -> bool {
+        current.is_running(caller)
+    }
+
+    // Mutex ID
+    fn params(&self) -> &[Param] {
+        &[Param::ObjectId(Object::Mutex)]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        let mut next = current.clone();
+        let mutex = next.get_mutex_mut(args[0]).unwrap();
+
+        if mutex.violates_ceiling(current.get_running_task(caller).unwrap().prio) {
+            return super::failure(current, errno::EINVAL);
+        }
+        // Unlike pthread_mutex_lock, the caller never blocks
+        if mutex.owner.is_some() {
+            return super::failure(current, errno::EBUSY);
+        }
+
+        mutex.owner = Some(caller);
+        next.update_priority(caller);
+        super::success(vec![next])
+    }
+}
+
+pub static FUNCTION: PthreadMutexTrylock = PthreadMutexTrylock;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::{
+        function::{get_function, Function},
+        scheduler::State,
+    };
+
+    #[test]
+    fn test_pthread_mutex_trylock() {
+        let states = State::new(2).create_task(1).schedule();
+        let transitions = get_function(Function::PthreadCreate).call(&states[0], 1, &[1, 1, 0, 0]);
+        let state = &transitions[0].next;
+        assert!(state.is_running(1) && state.is_running(2));
+
+        let trylock = get_function(Function::PthreadMutexTrylock);
+        let transitions = trylock.call(state, 1, &[0]);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].ret, errno::EOK);
+        let state = &transitions[0].next;
+        assert_eq!(state.mutexes[0].owner, Some(1));
+
+        // A busy mutex leaves the state unchanged
+        for caller in [1, 2] {
+            let transitions = trylock.call(state, caller, &[0]);
+            assert_eq!(transitions.len(), 1);
+            assert_eq!(transitions[0].ret, errno::EBUSY);
+            assert_eq!(&transitions[0].next, state);
+        }
+    }
+
+    #[test]
+    fn test_pthread_mutex_trylock_protect() {
+        let trylock = get_function(Function::PthreadMutexTrylock);
+        let state: State = "C0:T1/p3 C1:T2/p6 RQ:[] X:[] M0p5:-".parse().unwrap();
+
+        let transitions = trylock.call(&state, 1, &[0]);
+        assert_eq!(transitions[0].ret, errno::EOK);
+        assert_eq!(
+            transitions[0].next.to_string(),
+            "C0:T1/p5/b3 C1:T2/p6 RQ:[] X:[] M0p5:T1"
+        );
+
+        // The ceiling is checked before the owner
+        for state in [&state, &transitions[0].next] {
+            let transitions = trylock.call(state, 2, &[0]);
+            assert_eq!(transitions[0].ret, errno::EINVAL);
+            assert_eq!(&transitions[0].next, state);
+        }
+    }
+}
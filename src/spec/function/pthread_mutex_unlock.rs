@@ -0,0 +1,146 @@
+use super::Param;
+use crate::{
+    errno,
+    spec::{
+        scheduler,
+        sync::{MutexKind, Object},
+    },
+};
+
+pub struct PthreadMutexUnlock;
+
+impl super::Formalized for PthreadMutexUnlock {
+    // Unlocking a normal mutex that the caller does not own is undefined behavior
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> bool {
+        current.is_running(caller)
+            && match current.get_mutex(args[0]) {
+                Some(mutex) => mutex.kind != MutexKind::Normal || mutex.owner == Some(caller),
+                None => true,
+            }
+    }
+
+    // Mutex ID
+    fn params(&self) -> &[Param] {
+        &[Param::ObjectId(Object::Mutex)]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        if current.get_mutex(args[0]).unwrap().owner != Some(caller) {
+            return super::failure(current, errno::EPERM);
+        }
+
+        // The lock is handed over to the highest-priority waiter, which may preempt the caller
+        let mut next = current.clone();
+        next.release_mutex(args[0], caller);
+        super::success(next.schedule())
+    }
+}
+
+pub static FUNCTION: PthreadMutexUnlock = PthreadMutexUnlock;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::{
+        function::{call, get_function, ok, Function},
+        scheduler::State,
+    };
+
+    #[test]
+    fn test_pthread_mutex_unlock() {
+        let lock = get_function(Function::PthreadMutexLock);
+        let unlock = get_function(Function::PthreadMutexUnlock);
+        let create = get_function(Function::PthreadCreate);
+        let exit = get_function(Function::PthreadExit);
+
+        // Task 1 (prio 1) holds the mutex, tasks 2 (prio 3) and 3 (prio 2) wait for it
+        let state = State::new(1).create_task(1).schedule().remove(0);
+        let state = lock.call(&state, 1, &[0]).remove(0).next;
+        let state = create.call(&state, 1, &[3, 1, 0, 0]).remove(0).next;
+        let state = lock.call(&state, 2, &[0]).remove(0).next;
+        let state = create.call(&state, 1, &[2, 1, 0, 0]).remove(0).next;
+        let state = lock.call(&state, 3, &[0]).remove(0).next;
+        assert!(state.is_running(1));
+
+        // The lock goes to task 2 which preempts task 1
+        let transitions = unlock.call(&state, 1, &[0]);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].ret, errno::EOK);
+        let state = &transitions[0].next;
+        assert_eq!(state.mutexes[0].owner, Some(2));
+        assert!(state.is_running(2));
+        assert_eq!(
+            state.ready_queue.iter().map(|t| t.tid).collect::<Vec<_>>(),
+            vec![1]
+        );
+
+        let state = unlock.call(state, 2, &[0]).remove(0).next;
+        assert_eq!(state.mutexes[0].owner, Some(3));
+        assert!(state.is_running(2));
+
+        // Task 2 no longer owns the mutex
+        assert_eq!(unlock.call(&state, 2, &[0])[0].ret, errno::EPERM);
+
+        let state = exit.call(&state, 2, &[]).remove(0).next;
+        assert!(state.is_running(3));
+        let state = unlock.call(&state, 3, &[0]).remove(0).next;
+        assert_eq!(state.mutexes[0].owner, None);
+        assert_eq!(unlock.call(&state, 3, &[0])[0].ret, errno::EPERM);
+    }
+
+    #[test]
+    fn test_pthread_mutex_unlock_deboost() {
+        // Task 3 drops back to its base priority and is preempted by the new owner, which still
+        // inherits from task 4 through mutex 1
+        assert_eq!(
+            call(
+                Function::PthreadMutexUnlock,
+                "C0:T3/p5/b1 RQ:[] X:[] M0i:T3[T1/p5/b1] M1i:T1[T4/p5,T2/p2]",
+                3,
+                &[0]
+            ),
+            ok("C0:T1/p5/b1 RQ:[T3/p1] X:[] M0i:T1 M1i:T1[T4/p5,T2/p2]")
+        );
+        // The caller may drop below a task that was ready before
+        assert_eq!(
+            call(
+                Function::PthreadMutexUnlock,
+                "C0:T1/p4/b1 RQ:[T2/p2] X:[] M0i:T1[T3/p4]",
+                1,
+                &[0]
+            ),
+            ok("C0:T3/p4 RQ:[T2/p2,T1/p1] X:[] M0i:T3")
+        );
+        // It keeps what it inherits through the other mutex
+        assert_eq!(
+            call(
+                Function::PthreadMutexUnlock,
+                "C0:T1/p3/b1 RQ:[] X:[] M0i:T1 M1i:T1[T2/p3]",
+                1,
+                &[0]
+            ),
+            ok("C0:T1/p3/b1 RQ:[] X:[] M0i:- M1i:T1[T2/p3]")
+        );
+        // The owner of a priority-protection mutex drops back from its ceiling
+        assert_eq!(
+            call(
+                Function::PthreadMutexUnlock,
+                "C0:T1/p5/b1 RQ:[T2/p3] X:[] M0p5:T1",
+                1,
+                &[0]
+            ),
+            ok("C0:T2/p3 RQ:[T1/p1] X:[] M0p5:-")
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use super::Param;
+use crate::{errno, spec::scheduler};
+
+pub struct PthreadSetaffinity;
+
+// pthread_setaffinity_np
+impl super::Formalized for PthreadSetaffinity {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> bool {
+        current.is_running(caller) && current.tasks().any(|task| task.tid == args[0])
+    }
+
+    // TID, Affinity mask
+    fn params(&self) -> &[Param] {
+        &[Param::Tid, Param::Affinity]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if current.is_terminated(args[0]) {
+            return super::failure(current, errno::ESRCH);
+        }
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+        // Cores that do not exist are ignored, but one of the cores has to
+        let affinity = match current.normalize_affinity(args[1]) {
+            Some(affinity) => affinity,
+            None => return super::failure(current, errno::EINVAL),
+        };
+
+        super::success(current.set_affinity(args[0], affinity))
+    }
+}
+
+pub static FUNCTION: PthreadSetaffinity = PthreadSetaffinity;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::function::{assert_errors, call, ok, Function};
+
+    #[test]
+    fn test_pthread_setaffinity() {
+        // The caller migrates to the idle core it is restricted to
+        assert_eq!(
+            call(
+                Function::PthreadSetaffinity,
+                "C0:T1/p2 C1:- RQ:[] X:[] M0:-",
+                1,
+                &[1, 2]
+            ),
+            ok("C0:- C1:T1/p2/a2 RQ:[] X:[] M0:-")
+        );
+        // It preempts a task of a lower priority there, which takes over the core it left, but
+        // waits for one of the same priority
+        assert_eq!(
+            call(
+                Function::PthreadSetaffinity,
+                "C0:T1/p2 C1:T2/p1 RQ:[] X:[] M0:-",
+                1,
+                &[1, 2]
+            ),
+            ok("C0:T2/p1 C1:T1/p2/a2 RQ:[] X:[] M0:-")
+        );
+        assert_eq!(
+            call(
+                Function::PthreadSetaffinity,
+                "C0:T1/p2 C1:T2/p2 RQ:[T3/p1] X:[] M0:-",
+                1,
+                &[1, 2]
+            ),
+            ok("C0:T3/p1 C1:T2/p2 RQ:[T1/p2/a2] X:[] M0:-")
+        );
+        // A ready task restricted to a busy core of a lower priority preempts it
+        assert_eq!(
+            call(
+                Function::PthreadSetaffinity,
+                "C0:T1/p3 C1:T2/p1 RQ:[T3/p2] X:[] M0:-",
+                1,
+                &[3, 2]
+            ),
+            ok("C0:T1/p3 C1:T3/p2/a2 RQ:[T2/p1] X:[] M0:-")
+        );
+        // Allowing every core lifts the restriction, and cores beyond the CPU are ignored
+        assert_eq!(
+            call(
+                Function::PthreadSetaffinity,
+                "C0:T1/p1 C1:- RQ:[] X:[] M0:-",
+                1,
+                &[1, 15]
+            ),
+            ok("C0:T1/p1 C1:- RQ:[] X:[] M0:-")
+        );
+    }
+
+    #[test]
+    fn test_pthread_setaffinity_errors() {
+        let state = "C0:T1/p1 C1:- RQ:[] X:[T2/p1] M0:-";
+        assert_errors(
+            Function::PthreadSetaffinity,
+            1,
+            &[
+                (state, &[2, 1], errno::ESRCH),
+                (state, &[1, 4], errno::EINVAL),
+                (state, &[1, 16], errno::EINVAL),
+            ],
+        );
+    }
+}
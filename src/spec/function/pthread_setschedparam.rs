@@ -0,0 +1,106 @@
+use super::Param;
+use crate::{
+    errno,
+    spec::{sched_data::Policy, scheduler},
+};
+
+pub struct PthreadSetschedparam;
+
+impl super::Formalized for PthreadSetschedparam {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> bool {
+        current.is_running(caller) && current.tasks().any(|task| task.tid == args[0])
+    }
+
+    // TID, Policy (SCHED_FIFO or SCHED_RR), Priority
+    fn params(&self) -> &[Param] {
+        &[Param::Tid, Param::Policy, Param::Priority]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if current.is_terminated(args[0]) {
+            return super::failure(current, errno::ESRCH);
+        }
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        let policy = Policy::from_arg(args[1]).unwrap();
+        if !current.config.is_valid_priority(policy, args[2]) {
+            return super::failure(current, errno::EINVAL);
+        }
+        // The target goes to the tail of its new priority level
+        super::success(current.set_priority(args[0], args[2], policy, false))
+    }
+}
+
+pub static FUNCTION: PthreadSetschedparam = PthreadSetschedparam;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::{
+        function::{assert_errors, call, get_function, ok, Function},
+        scheduler::State,
+    };
+
+    #[test]
+    fn test_pthread_setschedparam() {
+        // A ready task goes to the tail of its new level and preempts a lower one
+        assert_eq!(
+            call(
+                Function::PthreadSetschedparam,
+                "C0:T1/p2 RQ:[T2/p3,T3/p1] X:[] M0:-",
+                1,
+                &[3, 1, 3]
+            ),
+            ok("C0:T2/p3 RQ:[T3/p3,T1/p2] X:[] M0:-")
+        );
+        // The same priority still moves the task to the tail
+        assert_eq!(
+            call(
+                Function::PthreadSetschedparam,
+                "C0:T1/p3 RQ:[T2/p2,T3/p2] X:[] M0:-",
+                1,
+                &[2, 2, 2]
+            ),
+            ok("C0:T1/p3 RQ:[T3/p2,T2/p2/rr] X:[] M0:-")
+        );
+        // A running task that lowers itself below a ready one is preempted to the tail
+        assert_eq!(
+            call(
+                Function::PthreadSetschedparam,
+                "C0:T1/p3 RQ:[T2/p2,T3/p2] X:[] M0:-",
+                1,
+                &[1, 1, 2]
+            ),
+            ok("C0:T2/p2 RQ:[T3/p2,T1/p2] X:[] M0:-")
+        );
+    }
+
+    #[test]
+    fn test_pthread_setschedparam_errors() {
+        let state = "C0:T1/p3 RQ:[T2/p2] X:[T3/p1] M0:-";
+        assert_errors(
+            Function::PthreadSetschedparam,
+            1,
+            &[
+                (state, &[3, 1, 2], errno::ESRCH),
+                (state, &[2, 1, 0], errno::EINVAL),
+                (state, &[2, 1, 100], errno::EINVAL),
+                (state, &[2, 3, 2], errno::EINVAL),
+            ],
+        );
+
+        let state: State = state.parse().unwrap();
+        let f = get_function(Function::PthreadSetschedparam);
+        assert!(!f.is_invokable(&state, 2, &[1, 1, 2]));
+        assert!(!f.is_invokable(&state, 1, &[4, 1, 2]));
+    }
+}
@@ -0,0 +1,116 @@
+use super::Param;
+use crate::{errno, spec::scheduler};
+
+pub struct PthreadSetschedprio;
+
+impl super::Formalized for PthreadSetschedprio {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, args: &[u32]) -> bool {
+        current.is_running(caller) && current.tasks().any(|task| task.tid == args[0])
+    }
+
+    // TID, Priority
+    fn params(&self) -> &[Param] {
+        &[Param::Tid, Param::Priority]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if current.is_terminated(args[0]) {
+            return super::failure(current, errno::ESRCH);
+        }
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        // A raised task goes to the tail of its new level, a lowered one to the head, and one
+        // whose priority is unchanged keeps its position
+        let task = current.tasks().find(|task| task.tid == args[0]).unwrap();
+        let (prio, policy) = (args[1], task.policy);
+        if !current.config.is_valid_priority(policy, prio) {
+            return super::failure(current, errno::EINVAL);
+        }
+        if prio == task.prio && prio == task.base_prio {
+            return super::success(vec![current.clone()]);
+        }
+        super::success(current.set_priority(args[0], prio, policy, prio < task.prio))
+    }
+}
+
+pub static FUNCTION: PthreadSetschedprio = PthreadSetschedprio;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::function::{assert_errors, call, ok, Function};
+
+    #[test]
+    fn test_pthread_setschedprio() {
+        let state = "C0:T1/p3 RQ:[T2/p2,T3/p2,T4/p1] X:[] M0:-";
+        for (args, expected) in [
+            // Raised: tail of the new level
+            ([4, 2], "C0:T1/p3 RQ:[T2/p2,T3/p2,T4/p2] X:[] M0:-"),
+            // Unchanged: no move
+            ([2, 2], state),
+            // Lowered: head of the new level
+            ([3, 1], "C0:T1/p3 RQ:[T2/p2,T3/p1,T4/p1] X:[] M0:-"),
+            // Raised above the running task, which is preempted to the head of its level
+            ([3, 4], "C0:T3/p4 RQ:[T1/p3,T2/p2,T4/p1] X:[] M0:-"),
+            // The running task lowers itself below a ready task
+            ([1, 1], "C0:T2/p2 RQ:[T3/p2,T1/p1,T4/p1] X:[] M0:-"),
+        ] {
+            assert_eq!(
+                call(Function::PthreadSetschedprio, state, 1, &args),
+                vec![(errno::EOK, expected.to_string())],
+                "{:?}",
+                args
+            );
+        }
+
+        // A task that inherits a priority keeps it, and its position, until its base priority
+        // exceeds it
+        let state = "C0:T1/p3 RQ:[T2/p2/b1,T3/p2] X:[] M0i:T2[T4/p2]";
+        for (args, expected) in [
+            ([2, 2], "C0:T1/p3 RQ:[T2/p2,T3/p2] X:[] M0i:T2[T4/p2]"),
+            ([2, 1], state),
+            ([2, 3], "C0:T1/p3 RQ:[T2/p3,T3/p2] X:[] M0i:T2[T4/p2]"),
+        ] {
+            assert_eq!(
+                call(Function::PthreadSetschedprio, state, 1, &args),
+                vec![(errno::EOK, expected.to_string())],
+                "{:?}",
+                args
+            );
+        }
+
+        // On two cores the other running task of the lowest priority is preempted
+        assert_eq!(
+            call(
+                Function::PthreadSetschedprio,
+                "C0:T1/p3 C1:T2/p1 RQ:[T3/p2] X:[] M0:-",
+                1,
+                &[1, 2]
+            ),
+            ok("C0:T1/p2 C1:T3/p2 RQ:[T2/p1] X:[] M0:-")
+        );
+    }
+
+    #[test]
+    fn test_pthread_setschedprio_errors() {
+        let state = "C0:T1/p3 RQ:[T2/p2] X:[T3/p1] M0:-";
+        assert_errors(
+            Function::PthreadSetschedprio,
+            1,
+            &[
+                (state, &[3, 2], errno::ESRCH),
+                (state, &[2, 0], errno::EINVAL),
+                (state, &[2, 100], errno::EINVAL),
+            ],
+        );
+    }
+}
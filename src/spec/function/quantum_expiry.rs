@@ -0,0 +1,68 @@
+use super::Param;
+use crate::spec::{sched_data::Policy, scheduler::State};
+
+pub struct QuantumExpiry;
+
+// Pseudo function that corresponds to the expiry of the time slice of the calling SCHED_RR task
+impl super::Formalized for QuantumExpiry {
+    fn is_invokable(&self, current: &State, caller: u32, _args: &[u32]) -> bool {
+        matches!(current.get_running_task(caller), Some(task) if task.policy == Policy::RoundRobin)
+    }
+
+    fn params(&self) -> &[Param] {
+        &[]
+    }
+
+    // The task goes to the tail of its priority level and may be dispatched again at once
+    fn call(&self, current: &State, caller: u32, args: &[u32]) -> Vec<super::Transition> {
+        assert!(super::check_args(self, current, args));
+        assert!(self.is_invokable(current, caller, args));
+
+        super::success(current.requeue(caller).schedule())
+    }
+}
+
+pub static FUNCTION: QuantumExpiry = QuantumExpiry;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::scenario::Scenario;
+    use crate::spec::{
+        config::ModelConfig,
+        function::{get_function, Function},
+        scheduler::State,
+    };
+
+    #[test]
+    fn test_quantum_expiry() {
+        let expiry = get_function(Function::QuantumExpiry);
+        let state: State = "C0:T2/p2/rr RQ:[T3/p2/rr,T4/p2,T1/p1] X:[] M0:-"
+            .parse()
+            .unwrap();
+        assert!(!expiry.is_invokable(&state, 1, &[]));
+        assert!(expiry.is_invokable(&state, 2, &[]));
+
+        let transitions = expiry.call(&state, 2, &[]);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].ret, errno::EOK);
+        assert_eq!(
+            transitions[0].next.to_string(),
+            "C0:T3/p2/rr RQ:[T4/p2,T2/p2/rr,T1/p1] X:[] M0:-"
+        );
+
+        // Without another task of the same priority, the task keeps running
+        let states = "cores 2; spawn; 1: pthread_create(3, 2, 0, 0); 2: quantum_expiry()"
+            .parse::<Scenario>()
+            .unwrap()
+            .run(ModelConfig::default())
+            .unwrap();
+        assert_eq!(
+            states.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+            vec![
+                "C0:T1/p1 C1:T2/p3/rr RQ:[] X:[] M0:-",
+                "C0:T2/p3/rr C1:T1/p1 RQ:[] X:[] M0:-"
+            ]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use super::Param;
+use crate::spec::scheduler;
+
+pub struct SchedYield;
+
+impl super::Formalized for SchedYield {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, _args: &[u32]) -> bool {
+        current.is_running(caller)
+    }
+
+    fn params(&self) -> &[Param] {
+        &[]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(super::check_args(self, current, args));
+        assert!(self.is_invokable(current, caller, args));
+
+        // The caller keeps its core unless another task of the same priority may take it over
+        let prio = current.get_running_task(caller).unwrap().prio;
+        let core_id = current.core_of(caller).unwrap();
+        if !matches!(current.highest_ready_for(core_id), Some(task) if task.prio == prio) {
+            return super::success(vec![current.clone()]);
+        }
+
+        super::success(current.requeue(caller).schedule())
+    }
+}
+
+pub static FUNCTION: SchedYield = SchedYield;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::{
+        function::{get_function, Function},
+        scheduler::State,
+    };
+
+    #[test]
+    fn test_sched_yield() {
+        let sched_yield = get_function(Function::SchedYield);
+
+        let state: State = "C0:T1/p2 C1:T2/p2 RQ:[T3/p2,T4/p2,T5/p1] X:[] M0:-"
+            .parse()
+            .unwrap();
+        let transitions = sched_yield.call(&state, 1, &[]);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].ret, errno::EOK);
+        assert_eq!(
+            transitions[0].next.to_string(),
+            "C0:T3/p2 C1:T2/p2 RQ:[T4/p2,T1/p2,T5/p1] X:[] M0:-"
+        );
+
+        // Only tasks of a lower priority are ready, or other cores are idle
+        for state in [
+            "C0:T1/p2 RQ:[T2/p1] X:[] M0:-",
+            "C0:T1/p2 C1:- C2:- RQ:[] X:[] M0:-",
+        ] {
+            let state: State = state.parse().unwrap();
+            let transitions = sched_yield.call(&state, 1, &[]);
+            assert_eq!(transitions.len(), 1);
+            assert_eq!(transitions[0].ret, errno::EOK);
+            assert_eq!(transitions[0].next, state);
+        }
+    }
+}
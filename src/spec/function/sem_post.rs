@@ -0,0 +1,88 @@
+use super::Param;
+use crate::{
+    errno,
+    spec::{sched_data::TaskState, scheduler, sync::Object},
+};
+
+pub struct SemPost;
+
+impl super::Formalized for SemPost {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, _args: &[u32]) -> bool {
+        current.is_running(caller)
+    }
+
+    // Semaphore ID
+    fn params(&self) -> &[Param] {
+        &[Param::ObjectId(Object::Sem)]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        // The highest-priority waiter takes the post and becomes ready, which may preempt the
+        // caller. Without waiters the count goes up
+        let mut next = current.clone();
+        let sem = next.get_sem_mut(args[0]).unwrap();
+        match sem.wait_queue.dequeue() {
+            Some(mut waiter) => {
+                waiter.state = TaskState::Ready;
+                next.ready_queue.enqueue_tail(waiter);
+                super::success(next.schedule())
+            }
+            None => {
+                sem.count += 1;
+                super::success(vec![next])
+            }
+        }
+    }
+}
+
+pub static FUNCTION: SemPost = SemPost;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::scenario::Scenario;
+    use crate::spec::{
+        config::ModelConfig,
+        function::{get_function, Function},
+        scheduler::State,
+    };
+
+    #[test]
+    fn test_sem_post() {
+        let states = "cores 1; spawn; 1: sem_post(0); 1: sem_post(0)"
+            .parse::<Scenario>()
+            .unwrap()
+            .run(ModelConfig::default())
+            .unwrap();
+        assert_eq!(
+            states.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+            vec!["C0:T1/p1 RQ:[] X:[] M0:- S0:2[]"]
+        );
+
+        // The waiter of the highest priority preempts the caller
+        let post = get_function(Function::SemPost);
+        let state: State = "C0:T1/p2 RQ:[] X:[] M0:- S0:0[T2/p3,T3/p1]"
+            .parse()
+            .unwrap();
+        let transitions = post.call(&state, 1, &[0]);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].ret, errno::EOK);
+        assert_eq!(
+            transitions[0].next.to_string(),
+            "C0:T2/p3 RQ:[T1/p2] X:[] M0:- S0:0[T3/p1]"
+        );
+
+        assert_eq!(post.call(&state, 1, &[1])[0].ret, errno::EINVAL);
+    }
+}
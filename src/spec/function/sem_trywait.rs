@@ -0,0 +1,70 @@
+use super::Param;
+use crate::{
+    errno,
+    spec::{scheduler, sync::Object},
+};
+
+pub struct SemTrywait;
+
+impl super::Formalized for SemTrywait {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, _args: &[u32]) -> bool {
+        current.is_running(caller)
+    }
+
+    // Semaphore ID
+    fn params(&self) -> &[Param] {
+        &[Param::ObjectId(Object::Sem)]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        // Unlike sem_wait, the caller never blocks
+        if current.get_sem(args[0]).unwrap().count == 0 {
+            return super::failure(current, errno::EAGAIN);
+        }
+
+        let mut next = current.clone();
+        next.get_sem_mut(args[0]).unwrap().count -= 1;
+        super::success(vec![next])
+    }
+}
+
+pub static FUNCTION: SemTrywait = SemTrywait;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::{
+        function::{get_function, Function},
+        scheduler::State,
+    };
+
+    #[test]
+    fn test_sem_trywait() {
+        let trywait = get_function(Function::SemTrywait);
+        let state: State = "C0:T1/p1 RQ:[] X:[] M0:- S0:1[]".parse().unwrap();
+
+        let transitions = trywait.call(&state, 1, &[0]);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].ret, errno::EOK);
+        let state = &transitions[0].next;
+        assert_eq!(state.to_string(), "C0:T1/p1 RQ:[] X:[] M0:-");
+
+        for (args, ret) in [([0], errno::EAGAIN), ([1], errno::EINVAL)] {
+            let transitions = trywait.call(state, 1, &args);
+            assert_eq!(transitions.len(), 1);
+            assert_eq!(transitions[0].ret, ret);
+            assert_eq!(&transitions[0].next, state);
+        }
+    }
+}
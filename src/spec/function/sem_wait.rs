@@ -0,0 +1,89 @@
+use super::Param;
+use crate::{
+    errno,
+    spec::{scheduler, sync::Object},
+};
+
+pub struct SemWait;
+
+impl super::Formalized for SemWait {
+    fn is_invokable(&self, current: &scheduler::State, caller: u32, _args: &[u32]) -> bool {
+        current.is_running(caller)
+    }
+
+    // Semaphore ID
+    fn params(&self) -> &[Param] {
+        &[Param::ObjectId(Object::Sem)]
+    }
+
+    fn call(
+        &self,
+        current: &scheduler::State,
+        caller: u32,
+        args: &[u32],
+    ) -> Vec<super::Transition> {
+        assert!(self.is_invokable(current, caller, args));
+
+        if !super::check_args(self, current, args) {
+            return super::failure(current, errno::EINVAL);
+        }
+
+        let mut next = current.clone();
+        let sem = next.get_sem_mut(args[0]).unwrap();
+        if sem.count > 0 {
+            sem.count -= 1;
+            return super::success(vec![next]);
+        }
+
+        // The caller sleeps until a post hands the semaphore to it
+        let task = next.block(caller);
+        next.get_sem_mut(args[0]).unwrap().wait_queue.enqueue(task);
+        super::success(next.schedule())
+    }
+}
+
+pub static FUNCTION: SemWait = SemWait;
+
+#[cfg(test)]
+mod tests {
+    use crate::errno;
+    use crate::spec::function::{assert_errors, call, ok, Function};
+
+    #[test]
+    fn test_sem_wait() {
+        assert_eq!(
+            call(
+                Function::SemWait,
+                "C0:T1/p2 RQ:[] X:[] M0:- S0:2[]",
+                1,
+                &[0]
+            ),
+            ok("C0:T1/p2 RQ:[] X:[] M0:- S0:1[]")
+        );
+        assert_eq!(
+            call(
+                Function::SemWait,
+                "C0:T1/p2 RQ:[] X:[] M0:- S0:1[]",
+                1,
+                &[0]
+            ),
+            ok("C0:T1/p2 RQ:[] X:[] M0:-")
+        );
+        // At 0 the caller waits behind the waiters of its priority
+        assert_eq!(
+            call(
+                Function::SemWait,
+                "C0:T1/p2 RQ:[T3/p1] X:[] M0:- S0:0[T2/p2]",
+                1,
+                &[0]
+            ),
+            ok("C0:T3/p1 RQ:[] X:[] M0:- S0:0[T2/p2,T1/p2]")
+        );
+
+        assert_errors(
+            Function::SemWait,
+            1,
+            &[("C0:T1/p2 RQ:[] X:[] M0:-", &[1], errno::EINVAL)],
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use crate::spec::scheduler::State;
+use super::Param;
+use crate::spec::{sched_data::ALL_CORES, scheduler::State, sync::Mutex};
 
 pub struct Spawn;
 
@@ -8,13 +9,34 @@ impl super::Formalized for Spawn {
         false
     }
 
-    fn args(&self) -> &[(u32, u32)] {
+    fn params(&self) -> &[Param] {
         &[]
     }
 
-    fn call(&self, state: &State, _: u32, _: &[u32]) -> Vec<State> {
-        let num_core = state.cpu.cores.len() as u32;
-        State::new(num_core).create_task(1).schedule()
+    // The main thread starts in the initial state, in which the mutexes are already initialized.
+    // Whatever the state holds is dropped but the cores, the config and the mutex attributes
+    fn call(&self, state: &State, _: u32, _: &[u32]) -> Vec<super::Transition> {
+        let config = state.config;
+        let mut initial = State::new(state.cpu.cores.len() as u32).with_config(config);
+        initial.mutexes = state
+            .mutexes
+            .iter()
+            .map(|m| Mutex {
+                protocol: m.protocol,
+                ceiling: m.ceiling,
+                ..Mutex::with_kind(m.id, m.kind)
+            })
+            .collect();
+        super::success(
+            initial
+                .create_task_with_attr(
+                    config.initial_priority,
+                    config.initial_policy,
+                    false,
+                    ALL_CORES,
+                )
+                .schedule(),
+        )
     }
 }
 
@@ -22,56 +44,44 @@ pub static FUNCTION: Spawn = Spawn;
 
 #[cfg(test)]
 mod tests {
+    use crate::errno;
     use crate::spec::{
-        cpu::{Core, CPU},
+        config::ModelConfig,
         function::{get_function, Function},
-        sched_data::{ReadyQueue, TaskControlBlock, TaskState},
+        sched_data::Policy,
         scheduler::State,
     };
+    use std::str::FromStr;
 
     #[test]
     fn test_spawn() {
         let spawn = get_function(Function::Spawn);
-        let states = spawn.call(&State::new(2), 0, &[]);
+        let transitions = spawn.call(&State::new(2), 0, &[]);
+        assert!(transitions.iter().all(|t| t.ret == errno::EOK));
+        let states: Vec<String> = transitions.iter().map(|t| t.next.to_string()).collect();
 
         assert_eq!(
             states,
             vec![
-                State {
-                    cpu: CPU {
-                        cores: vec![
-                            Core {
-                                id: 0,
-                                task: Some(TaskControlBlock {
-                                    tid: 1,
-                                    prio: 1,
-                                    state: TaskState::Running,
-                                },),
-                            },
-                            Core { id: 1, task: None },
-                        ],
-                    },
-                    ready_queue: ReadyQueue::new(),
-                    terminated_tasks: vec![],
-                },
-                State {
-                    cpu: CPU {
-                        cores: vec![
-                            Core { id: 0, task: None },
-                            Core {
-                                id: 1,
-                                task: Some(TaskControlBlock {
-                                    tid: 1,
-                                    prio: 1,
-                                    state: TaskState::Running,
-                                },),
-                            },
-                        ],
-                    },
-                    ready_queue: ReadyQueue::new(),
-                    terminated_tasks: vec![],
-                },
+                "C0:T1/p1 C1:- RQ:[] X:[] M0:-",
+                "C0:- C1:T1/p1 RQ:[] X:[] M0:-"
             ]
         );
+
+        let config = ModelConfig {
+            initial_priority: 10,
+            initial_policy: Policy::RoundRobin,
+            ..ModelConfig::default()
+        };
+        let transitions = spawn.call(&State::new(1).with_config(config), 0, &[]);
+        assert_eq!(
+            transitions[0].next.to_string(),
+            "C0:T1/p10/rr RQ:[] X:[] M0:-"
+        );
+
+        // A second spawn starts over instead of adding a task
+        let state = State::from_str("C0:T1/p1 RQ:[T2/p3] X:[] M0:T1").unwrap();
+        let transitions = spawn.call(&state, 0, &[]);
+        assert_eq!(transitions[0].next.to_string(), "C0:T1/p1 RQ:[] X:[] M0:-");
     }
 }
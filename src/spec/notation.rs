@@ -0,0 +1,362 @@
+// One-line notation of the scheduler state, e.g. "C0:T1/p1 C1:- RQ:[T3/p2,T1/p1] X:[T2/p1] M0:T1[T4/p1]".
+//
+//   C<id>:<task>|-            a core and the task running on it
+//   RQ:[<task>,..]            the ready queue from its head
+//   X:[<task>,..]             the terminated tasks
+//   M<id>[n][i|p<ceiling>]:<tid>|-[<task>,..]
+//                             a mutex ('n' for the normal kind, 'i' for priority inheritance, 'p'
+//                             for priority protection with its ceiling), its owner and wait queue
+//   CV<id>:M<id>[<task>,..]   a condition variable with waiters, the mutex they released and
+//                             their queue; one without waiters is left out
+//   S<id>:<count>[<task>,..]  a semaphore with its count and waiters; one at 0 without waiters
+//                             is left out
+//   J<tid>:<task>             a task blocked in pthread_join on thread <tid>
+//   T<tid>/p<prio>[/b<prio>][/rr][/d][/a<mask>]
+//                             a task ('b' with its base priority if it inherits a higher one,
+//                             'rr' for SCHED_RR, 'd' if detached, 'a' with the cores it is
+//                             restricted to), whose state follows from where it is
+//
+// A mutex, condition variable or semaphore that is left out is the one State::new starts with.
+// The model config is not part of the notation; a parsed state has the default one, and
+// State::with_config gives it another
+use std::fmt;
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use crate::spec::{
+    config::ModelConfig,
+    cpu::{Core, CPU, MAX_CORES},
+    sched_data::{Policy, ReadyQueue, TaskControlBlock, TaskState, WaitQueue, ALL_CORES},
+    scheduler::State,
+    sync::{self, CondVar, Mutex, MutexKind, MutexProtocol, Semaphore},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseStateError(pub String);
+
+impl fmt::Display for ParseStateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid state: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseStateError {}
+
+impl fmt::Display for TaskControlBlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "T{}/p{}", self.tid, self.prio)?;
+        if self.base_prio != self.prio {
+            write!(f, "/b{}", self.base_prio)?;
+        }
+        if self.policy == Policy::RoundRobin {
+            write!(f, "/rr")?;
+        }
+        if self.detached {
+            write!(f, "/d")?;
+        }
+        if self.affinity != ALL_CORES {
+            write!(f, "/a{}", self.affinity)?;
+        }
+        Ok(())
+    }
+}
+
+fn fmt_tasks<'a>(mut tasks: impl Iterator<Item = &'a TaskControlBlock>) -> String {
+    format!("[{}]", tasks.join(","))
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut tokens = vec![];
+        for core in self.cpu.cores.iter() {
+            match &core.task {
+                Some(task) => tokens.push(format!("C{}:{}", core.id, task)),
+                None => tokens.push(format!("C{}:-", core.id)),
+            }
+        }
+        tokens.push(format!("RQ:{}", fmt_tasks(self.ready_queue.iter())));
+        tokens.push(format!("X:{}", fmt_tasks(self.terminated_tasks.iter())));
+        for mutex in self.mutexes.iter() {
+            let kind = match mutex.kind {
+                MutexKind::Normal => "n",
+                MutexKind::ErrorCheck => "",
+            };
+            let protocol = match mutex.protocol {
+                MutexProtocol::PrioNone => String::new(),
+                MutexProtocol::PrioInherit => "i".to_string(),
+                MutexProtocol::PrioProtect => format!("p{}", mutex.ceiling),
+            };
+            let owner = mutex
+                .owner
+                .map_or("-".to_string(), |tid| format!("T{}", tid));
+            let waiters = if mutex.wait_queue.0.is_empty() {
+                String::new()
+            } else {
+                fmt_tasks(mutex.wait_queue.iter())
+            };
+            tokens.push(format!(
+                "M{}{}{}:{}{}",
+                mutex.id, kind, protocol, owner, waiters
+            ));
+        }
+        for cond in self.conds.iter() {
+            if let Some(mutex) = cond.mutex {
+                tokens.push(format!(
+                    "CV{}:M{}{}",
+                    cond.id,
+                    mutex,
+                    fmt_tasks(cond.wait_queue.iter())
+                ));
+            }
+        }
+        for sem in self.sems.iter() {
+            if sem.count > 0 || !sem.wait_queue.0.is_empty() {
+                tokens.push(format!(
+                    "S{}:{}{}",
+                    sem.id,
+                    sem.count,
+                    fmt_tasks(sem.wait_queue.iter())
+                ));
+            }
+        }
+        for (target, joiner) in self.joiners.iter() {
+            tokens.push(format!("J{}:{}", target, joiner));
+        }
+        write!(f, "{}", tokens.join(" "))
+    }
+}
+
+fn invalid(token: &str) -> ParseStateError {
+    ParseStateError(format!("`{}`", token))
+}
+
+fn parse_tid(s: &str) -> Result<u32, ParseStateError> {
+    s.strip_prefix('T')
+        .and_then(|tid| tid.parse().ok())
+        .ok_or_else(|| invalid(s))
+}
+
+fn parse_task(s: &str, state: TaskState) -> Result<TaskControlBlock, ParseStateError> {
+    let mut fields = s.split('/');
+    let tid = parse_tid(fields.next().unwrap())?;
+    let prio = fields
+        .next()
+        .and_then(|prio| prio.strip_prefix('p'))
+        .and_then(|prio| prio.parse().ok())
+        .ok_or_else(|| invalid(s))?;
+
+    let mut task = TaskControlBlock::new(tid, prio);
+    task.state = state;
+    for flag in fields {
+        if let Some(base_prio) = flag.strip_prefix('b') {
+            task.base_prio = base_prio.parse().map_err(|_| invalid(s))?;
+            continue;
+        }
+        match flag {
+            "rr" => task.policy = Policy::RoundRobin,
+            "d" => task.detached = true,
+            _ => match flag.strip_prefix('a').and_then(|mask| mask.parse().ok()) {
+                Some(affinity) => task.affinity = affinity,
+                None => return Err(invalid(s)),
+            },
+        }
+    }
+    Ok(task)
+}
+
+fn parse_tasks(s: &str, state: TaskState) -> Result<Vec<TaskControlBlock>, ParseStateError> {
+    let tasks = s
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .ok_or_else(|| invalid(s))?;
+    tasks
+        .split(',')
+        .filter(|task| !task.is_empty())
+        .map(|task| parse_task(task, state))
+        .collect()
+}
+
+fn parse_mutex(key: &str, value: &str) -> Result<Mutex, ParseStateError> {
+    let (id, flags) = key.split_at(key.find(|c: char| !c.is_ascii_digit()).unwrap_or(key.len()));
+    let mut mutex = Mutex::new(id.parse().map_err(|_| invalid(key))?);
+    let mut flags = flags;
+    while let Some(flag) = flags.chars().next() {
+        flags = &flags[1..];
+        match flag {
+            'n' => mutex.kind = MutexKind::Normal,
+            'i' => mutex.protocol = MutexProtocol::PrioInherit,
+            'p' => {
+                let len = flags
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(flags.len());
+                mutex.protocol = MutexProtocol::PrioProtect;
+                mutex.ceiling = flags[..len].parse().map_err(|_| invalid(key))?;
+                flags = &flags[len..];
+            }
+            _ => return Err(invalid(key)),
+        }
+    }
+
+    let (owner, waiters) = match value.find('[') {
+        Some(i) => value.split_at(i),
+        None => (value, "[]"),
+    };
+    if owner != "-" {
+        mutex.owner = Some(parse_tid(owner)?);
+    }
+    mutex.wait_queue = WaitQueue(parse_tasks(waiters, TaskState::Waiting)?.into());
+    Ok(mutex)
+}
+
+fn parse_cond(id: &str, value: &str) -> Result<CondVar, ParseStateError> {
+    let mut cond = CondVar::new(id.parse().map_err(|_| invalid(id))?);
+    let (mutex, waiters) = value.split_at(value.find('[').ok_or_else(|| invalid(value))?);
+    let mutex = mutex
+        .strip_prefix('M')
+        .and_then(|mutex| mutex.parse().ok())
+        .ok_or_else(|| invalid(value))?;
+    cond.mutex = Some(mutex);
+    cond.wait_queue = WaitQueue(parse_tasks(waiters, TaskState::Waiting)?.into());
+    Ok(cond)
+}
+
+fn parse_sem(id: &str, value: &str) -> Result<Semaphore, ParseStateError> {
+    let mut sem = Semaphore::new(id.parse().map_err(|_| invalid(id))?);
+    let (count, waiters) = value.split_at(value.find('[').ok_or_else(|| invalid(value))?);
+    sem.count = count.parse().map_err(|_| invalid(value))?;
+    sem.wait_queue = WaitQueue(parse_tasks(waiters, TaskState::Waiting)?.into());
+    Ok(sem)
+}
+
+impl FromStr for State {
+    type Err = ParseStateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut state = State {
+            cpu: CPU { cores: vec![] },
+            ready_queue: ReadyQueue::new(),
+            terminated_tasks: vec![],
+            mutexes: (0..sync::NUM_MUTEXES).map(Mutex::new).collect(),
+            conds: (0..sync::NUM_CONDS).map(CondVar::new).collect(),
+            sems: (0..sync::NUM_SEMS).map(Semaphore::new).collect(),
+            joiners: vec![],
+            config: ModelConfig::default(),
+        };
+
+        for token in s.split_whitespace() {
+            let (key, value) = token.split_once(':').ok_or_else(|| invalid(token))?;
+            if key == "RQ" {
+                state.ready_queue = ReadyQueue(parse_tasks(value, TaskState::Ready)?.into());
+            } else if key == "X" {
+                state.terminated_tasks = parse_tasks(value, TaskState::Terminated)?;
+            } else if let Some(id) = key.strip_prefix("CV") {
+                let cond = parse_cond(id, value)?;
+                match state.conds.iter_mut().find(|c| c.id == cond.id) {
+                    Some(c) => *c = cond,
+                    None => state.conds.push(cond),
+                }
+            } else if let Some(id) = key.strip_prefix('C') {
+                let task = match value {
+                    "-" => None,
+                    task => Some(parse_task(task, TaskState::Running)?),
+                };
+                // An affinity mask cannot name a core beyond MAX_CORES
+                let id = id
+                    .parse()
+                    .ok()
+                    .filter(|&id| id < MAX_CORES)
+                    .ok_or_else(|| invalid(token))?;
+                state.cpu.cores.push(Core { id, task });
+            } else if let Some(id) = key.strip_prefix('M') {
+                let mutex = parse_mutex(id, value)?;
+                match state.mutexes.iter_mut().find(|m| m.id == mutex.id) {
+                    Some(m) => *m = mutex,
+                    None => state.mutexes.push(mutex),
+                }
+            } else if let Some(id) = key.strip_prefix('S') {
+                let sem = parse_sem(id, value)?;
+                match state.sems.iter_mut().find(|s| s.id == sem.id) {
+                    Some(s) => *s = sem,
+                    None => state.sems.push(sem),
+                }
+            } else if let Some(target) = key.strip_prefix('J') {
+                let target = target.parse().map_err(|_| invalid(token))?;
+                state
+                    .joiners
+                    .push((target, parse_task(value, TaskState::Waiting)?));
+            } else {
+                return Err(invalid(token));
+            }
+        }
+
+        if state.cpu.cores.is_empty() {
+            return Err(ParseStateError("no cores".to_string()));
+        }
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::spec::{
+        sched_data::Policy,
+        scheduler::State,
+        sync::{MutexKind, MutexProtocol, Object},
+    };
+
+    #[test]
+    fn test_state_notation() {
+        let state = State::new(2).create_task(1).schedule().remove(0);
+        assert_eq!(state.to_string(), "C0:T1/p1 C1:- RQ:[] X:[] M0:-");
+        assert_eq!("C0:T1/p1 C1:- RQ:[] X:[] M0:-".parse(), Ok(state));
+
+        // The objects left out are those of State::new
+        let full: State = State::new(1).to_string().parse().unwrap();
+        let short: State = "C0:-".parse().unwrap();
+        assert_eq!(short, full);
+        for object in [Object::Mutex, Object::Cond, Object::Sem] {
+            assert_eq!(short.object_ids(object), full.object_ids(object));
+            assert!(!short.object_ids(object).is_empty());
+        }
+
+        let text =
+            "C0:T1/p3/b1 C1:- RQ:[T3/p2/rr,T4/p1/d] X:[T2/p1/rr/d/a5] M0ni:T1[T5/p2,T6/p1] M1p5:- CV0:M1[T8/p2] S0:0[T9/p1] J4:T7/p1";
+        let state: State = text.parse().unwrap();
+        assert_eq!(state.to_string(), text);
+        assert_eq!(state.ready_queue.0[0].policy, Policy::RoundRobin);
+        assert_eq!(state.mutexes[0].kind, MutexKind::Normal);
+        assert_eq!(state.mutexes[0].protocol, MutexProtocol::PrioInherit);
+        assert_eq!(state.mutexes[1].protocol, MutexProtocol::PrioProtect);
+        assert_eq!(state.mutexes[1].ceiling, 5);
+        assert_eq!(state.cpu.cores[0].task.as_ref().unwrap().base_prio, 1);
+        assert_eq!(state.mutexes[0].owner, Some(1));
+        assert!(state.ready_queue.0[1].detached);
+        assert_eq!(state.joiners[0].0, 4);
+        assert_eq!(state.conds[0].mutex, Some(1));
+        assert_eq!(state.sems[0].wait_queue.iter().next().unwrap().tid, 9);
+        assert_eq!(state.next_tid(), 10);
+        assert_eq!(
+            "C0:T1/p1 RQ:[] X:[] S0:2[]".parse::<State>().unwrap().sems[0].count,
+            2
+        );
+
+        for text in [
+            "",
+            "RQ:[]",
+            "C0:T1",
+            "C0:- RQ:T1/p1",
+            "C0:- Y:[]",
+            "C0:- M0:1",
+            "C0:- M0x:-",
+            "C0:- M0p:-",
+            "C0:- CV0:M0",
+            "C0:- CV0:-[]",
+            "C0:- S0:1",
+            "C32:-",
+            "C0:T1/p1/bx",
+        ] {
+            assert!(text.parse::<State>().is_err(), "{}", text);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::VecDeque;
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum TaskState {
     New,
     Ready,
@@ -9,10 +9,39 @@ pub enum TaskState {
     Waiting,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+// SCHED_FIFO and SCHED_RR, numbered as in Linux
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Policy {
+    Fifo = 1,
+    RoundRobin = 2,
+}
+
+impl Policy {
+    pub(crate) fn from_arg(arg: u32) -> Option<Policy> {
+        match arg {
+            1 => Some(Policy::Fifo),
+            2 => Some(Policy::RoundRobin),
+            _ => None,
+        }
+    }
+}
+
+// The affinity of a task that may run on any core
+pub(crate) const ALL_CORES: u32 = u32::MAX;
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub(crate) struct TaskControlBlock {
     pub(crate) tid: u32,
+    // The effective priority, which the task may inherit from the waiters of a mutex it owns
     pub(crate) prio: u32,
+    // The priority given to pthread_create or set by the task itself
+    pub(crate) base_prio: u32,
+    pub(crate) policy: Policy,
+    // A detached thread cannot be joined; a joinable one stays a zombie after it exits until
+    // it is joined, after which it is marked detached as well
+    pub(crate) detached: bool,
+    // The cores the task may run on, one bit per core ID
+    pub(crate) affinity: u32,
     pub(crate) state: TaskState,
 }
 
@@ -21,12 +50,51 @@ impl TaskControlBlock {
         TaskControlBlock {
             tid,
             prio,
+            base_prio: prio,
+            policy: Policy::Fifo,
+            detached: false,
+            affinity: ALL_CORES,
             state: TaskState::New,
         }
     }
+
+    pub(crate) fn can_run_on(&self, core_id: u32) -> bool {
+        self.affinity & 1 << core_id != 0
+    }
+}
+
+// Inserts a task behind every task with the same or higher priority
+fn insert_by_priority(queue: &mut VecDeque<TaskControlBlock>, new_task: TaskControlBlock) {
+    if queue.is_empty() {
+        queue.push_back(new_task);
+        return;
+    }
+
+    if let Some(front_task) = queue.front() {
+        if new_task.prio > front_task.prio {
+            queue.push_front(new_task);
+            return;
+        }
+    }
+
+    for pos in (1..=queue.len()).rev() {
+        if queue[pos - 1].prio >= new_task.prio {
+            queue.insert(pos, new_task);
+            break;
+        }
+    }
+}
+
+// Inserts a task in front of every task with the same or lower priority
+fn insert_by_priority_at_head(queue: &mut VecDeque<TaskControlBlock>, new_task: TaskControlBlock) {
+    let pos = queue
+        .iter()
+        .position(|task| task.prio <= new_task.prio)
+        .unwrap_or(queue.len());
+    queue.insert(pos, new_task);
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub(crate) struct ReadyQueue(pub(crate) VecDeque<TaskControlBlock>);
 
 impl ReadyQueue {
@@ -34,36 +102,59 @@ impl ReadyQueue {
         ReadyQueue(VecDeque::new())
     }
 
-    pub(crate) fn enqueue(&mut self, new_task: TaskControlBlock) {
-        if self.0.is_empty() {
-            self.0.push_back(new_task);
-            return;
-        }
+    // A new or woken task, or one that yields or has its priority changed, goes to the tail
+    // of its priority level
+    pub(crate) fn enqueue_tail(&mut self, new_task: TaskControlBlock) {
+        insert_by_priority(&mut self.0, new_task);
+    }
 
-        if let Some(front_task) = self.front() {
-            if new_task.prio > front_task.prio {
-                self.0.push_front(new_task);
-                return;
-            }
-        }
+    // A preempted task goes to the head of its priority level
+    pub(crate) fn enqueue_head(&mut self, new_task: TaskControlBlock) {
+        insert_by_priority_at_head(&mut self.0, new_task);
+    }
 
-        for pos in (1..=self.0.len()).rev() {
-            if self.0[pos - 1].prio >= new_task.prio {
-                self.0.insert(pos, new_task);
-                break;
-            }
-        }
+    // Removes the first task that satisfies the predicate, e.g. one that may run on an idle core
+    pub(crate) fn dequeue_where<P>(&mut self, predicate: P) -> Option<TaskControlBlock>
+    where
+        P: FnMut(&TaskControlBlock) -> bool,
+    {
+        let pos = self.0.iter().position(predicate)?;
+        self.0.remove(pos)
     }
 
-    pub(crate) fn front(&self) -> Option<&TaskControlBlock> {
-        self.0.front()
+    pub(crate) fn remove(&mut self, tid: u32) -> Option<TaskControlBlock> {
+        let pos = self.0.iter().position(|task| task.tid == tid)?;
+        self.0.remove(pos)
+    }
+
+    pub(crate) fn iter(&self) -> std::collections::vec_deque::Iter<'_, TaskControlBlock> {
+        self.0.iter()
+    }
+}
+
+// Tasks blocked on a synchronization object, ordered in the same way as the ready queue
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct WaitQueue(pub(crate) VecDeque<TaskControlBlock>);
+
+impl WaitQueue {
+    pub(crate) const fn new() -> Self {
+        WaitQueue(VecDeque::new())
+    }
+
+    pub(crate) fn enqueue(&mut self, new_task: TaskControlBlock) {
+        insert_by_priority(&mut self.0, new_task);
     }
 
     pub(crate) fn dequeue(&mut self) -> Option<TaskControlBlock> {
         self.0.pop_front()
     }
 
-    pub(crate) fn iter(&self) -> std::collections::vec_deque::Iter<TaskControlBlock> {
+    pub(crate) fn remove(&mut self, tid: u32) -> Option<TaskControlBlock> {
+        let pos = self.0.iter().position(|task| task.tid == tid)?;
+        self.0.remove(pos)
+    }
+
+    pub(crate) fn iter(&self) -> std::collections::vec_deque::Iter<'_, TaskControlBlock> {
         self.0.iter()
     }
 }
@@ -96,7 +187,7 @@ mod tests {
             let mut queue = ReadyQueue::new();
 
             for (i, prio) in input.into_iter().enumerate() {
-                queue.enqueue(TaskControlBlock::new((i + 1) as u32, prio as u32))
+                queue.enqueue_tail(TaskControlBlock::new((i + 1) as u32, prio as u32))
             }
 
             for (i, _) in queue.0.iter().enumerate() {
@@ -114,33 +205,49 @@ mod tests {
         let task1 = TaskControlBlock {
             tid: 1,
             prio: 1,
+            base_prio: 1,
+            policy: Policy::Fifo,
+            detached: false,
+            affinity: ALL_CORES,
             state: TaskState::Ready,
         };
 
         let task2 = TaskControlBlock {
             tid: 2,
             prio: 2,
+            base_prio: 2,
+            policy: Policy::Fifo,
+            detached: false,
+            affinity: ALL_CORES,
             state: TaskState::Ready,
         };
 
         let task3 = TaskControlBlock {
             tid: 3,
             prio: 3,
+            base_prio: 3,
+            policy: Policy::Fifo,
+            detached: false,
+            affinity: ALL_CORES,
             state: TaskState::Ready,
         };
 
         let task4 = TaskControlBlock {
             tid: 4,
             prio: 2,
+            base_prio: 2,
+            policy: Policy::Fifo,
+            detached: false,
+            affinity: ALL_CORES,
             state: TaskState::New,
         };
 
-        queue.enqueue(task1.clone());
-        queue.enqueue(task2.clone());
-        queue.enqueue(task3.clone());
-        queue.enqueue(task4.clone());
+        queue.enqueue_tail(task1.clone());
+        queue.enqueue_tail(task2.clone());
+        queue.enqueue_tail(task3.clone());
+        queue.enqueue_tail(task4.clone());
 
-        let expected_order = vec![task3, task2, task4, task1];
+        let expected_order = [task3, task2, task4, task1];
 
         for (task, expected_task) in queue.0.iter().zip(expected_order.iter()) {
             assert_eq!(task, expected_task);
@@ -154,39 +261,76 @@ mod tests {
         let task1 = TaskControlBlock {
             tid: 1,
             prio: 1,
+            base_prio: 1,
+            policy: Policy::Fifo,
+            detached: false,
+            affinity: ALL_CORES,
             state: TaskState::Ready,
         };
 
         let task2 = TaskControlBlock {
             tid: 2,
             prio: 2,
+            base_prio: 2,
+            policy: Policy::Fifo,
+            detached: false,
+            affinity: ALL_CORES,
             state: TaskState::Ready,
         };
 
         let task3 = TaskControlBlock {
             tid: 3,
             prio: 3,
+            base_prio: 3,
+            policy: Policy::Fifo,
+            detached: false,
+            affinity: ALL_CORES,
             state: TaskState::Ready,
         };
 
         let task4 = TaskControlBlock {
             tid: 4,
             prio: 2,
+            base_prio: 2,
+            policy: Policy::Fifo,
+            detached: false,
+            affinity: ALL_CORES,
             state: TaskState::New,
         };
 
-        queue.enqueue(task1.clone());
-        queue.enqueue(task2.clone());
-        queue.enqueue(task3.clone());
-        queue.enqueue(task4.clone());
+        queue.enqueue_tail(task1.clone());
+        queue.enqueue_tail(task2.clone());
+        queue.enqueue_tail(task3.clone());
+        queue.enqueue_tail(task4.clone());
 
-        let task = queue.dequeue().unwrap();
+        let task = queue.dequeue_where(|_| true).unwrap();
         assert_eq!(task, task3);
 
-        let expected_order = vec![task2, task4, task1];
+        let expected_order = [task2, task4, task1];
 
         for (task, expected_task) in queue.0.iter().zip(expected_order.iter()) {
             assert_eq!(task, expected_task, "queue: {:?}", queue);
         }
     }
+
+    #[test]
+    fn test_enqueue_head_and_tail() {
+        let mut queue = ReadyQueue::new();
+        for (tid, prio) in [(1, 2), (2, 3), (3, 2), (4, 1)] {
+            queue.enqueue_tail(TaskControlBlock::new(tid, prio));
+        }
+        let tids = |queue: &ReadyQueue| queue.iter().map(|t| t.tid).collect::<Vec<_>>();
+        assert_eq!(tids(&queue), vec![2, 1, 3, 4]);
+
+        // Ahead of the tasks of the same priority, but behind those of a higher one
+        queue.enqueue_head(TaskControlBlock::new(5, 2));
+        assert_eq!(tids(&queue), vec![2, 5, 1, 3, 4]);
+        queue.enqueue_tail(TaskControlBlock::new(6, 2));
+        assert_eq!(tids(&queue), vec![2, 5, 1, 3, 6, 4]);
+
+        queue.enqueue_head(TaskControlBlock::new(7, 4));
+        queue.enqueue_head(TaskControlBlock::new(8, 1));
+        queue.enqueue_tail(TaskControlBlock::new(9, 0));
+        assert_eq!(tids(&queue), vec![7, 2, 5, 1, 3, 6, 8, 4, 9]);
+    }
 }
@@ -1,11 +1,24 @@
 use super::sched_data::ReadyQueue;
-use crate::spec::{cpu::CPU, sched_data};
+use crate::spec::{
+    config::ModelConfig,
+    cpu::CPU,
+    sched_data,
+    sched_data::WaitQueue,
+    sync::{self, CondVar, Mutex, MutexProtocol, Semaphore},
+};
+use itertools::Itertools;
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct State {
     pub(crate) cpu: CPU,
     pub(crate) ready_queue: sched_data::ReadyQueue,
     pub(crate) terminated_tasks: Vec<sched_data::TaskControlBlock>,
+    pub(crate) mutexes: Vec<Mutex>,
+    pub(crate) conds: Vec<CondVar>,
+    pub(crate) sems: Vec<Semaphore>,
+    // Tasks blocked in pthread_join with the TID of the thread they wait for
+    pub(crate) joiners: Vec<(u32, sched_data::TaskControlBlock)>,
+    pub(crate) config: ModelConfig,
 }
 
 impl State {
@@ -14,9 +27,415 @@ impl State {
             cpu: CPU::new(num_core),
             ready_queue: ReadyQueue::new(),
             terminated_tasks: Vec::new(),
+            mutexes: (0..sync::NUM_MUTEXES).map(Mutex::new).collect(),
+            conds: (0..sync::NUM_CONDS).map(CondVar::new).collect(),
+            sems: (0..sync::NUM_SEMS).map(Semaphore::new).collect(),
+            joiners: Vec::new(),
+            config: ModelConfig::default(),
         }
     }
 
+    pub fn with_config(mut self, config: ModelConfig) -> State {
+        self.config = config;
+        self
+    }
+
+    // Gives every mutex the protocol, e.g. PTHREAD_PRIO_INHERIT
+    pub fn with_mutex_protocol(mut self, protocol: MutexProtocol) -> State {
+        for mutex in self.mutexes.iter_mut() {
+            mutex.protocol = protocol;
+        }
+        self
+    }
+
+    // Gives every mutex the priority ceiling used by PTHREAD_PRIO_PROTECT
+    pub fn with_mutex_ceiling(mut self, ceiling: u32) -> State {
+        for mutex in self.mutexes.iter_mut() {
+            mutex.ceiling = ceiling;
+        }
+        self
+    }
+
+    // Iterates over every task in the system regardless of its state
+    pub(crate) fn tasks(&self) -> impl Iterator<Item = &sched_data::TaskControlBlock> {
+        self.cpu
+            .cores
+            .iter()
+            .filter_map(|core| core.task.as_ref())
+            .chain(self.ready_queue.iter())
+            .chain(self.mutexes.iter().flat_map(|m| m.wait_queue.iter()))
+            .chain(self.conds.iter().flat_map(|c| c.wait_queue.iter()))
+            .chain(self.sems.iter().flat_map(|s| s.wait_queue.iter()))
+            .chain(self.joiners.iter().map(|(_, joiner)| joiner))
+            .chain(self.terminated_tasks.iter())
+    }
+
+    // The task if it is running, ready or blocked
+    fn live_task_mut(&mut self, tid: u32) -> Option<&mut sched_data::TaskControlBlock> {
+        let cores = self
+            .cpu
+            .cores
+            .iter_mut()
+            .filter_map(|core| core.task.as_mut());
+        let ready = self.ready_queue.0.iter_mut();
+        let waiting = self
+            .mutexes
+            .iter_mut()
+            .flat_map(|m| m.wait_queue.0.iter_mut())
+            .chain(
+                self.conds
+                    .iter_mut()
+                    .map(|c| &mut c.wait_queue)
+                    .chain(self.sems.iter_mut().map(|s| &mut s.wait_queue))
+                    .flat_map(|queue| queue.0.iter_mut()),
+            );
+        let joiners = self.joiners.iter_mut().map(|(_, joiner)| joiner);
+        cores
+            .chain(ready)
+            .chain(waiting)
+            .chain(joiners)
+            .find(|task| task.tid == tid)
+    }
+
+    pub(crate) fn is_running(&self, tid: u32) -> bool {
+        self.cpu
+            .cores
+            .iter()
+            .any(|core| matches!(&core.task, Some(task) if task.tid == tid))
+    }
+
+    // The IDs of the objects of the kind
+    pub(crate) fn object_ids(&self, object: sync::Object) -> Vec<u32> {
+        match object {
+            sync::Object::Mutex => self.mutexes.iter().map(|m| m.id).collect(),
+            sync::Object::Cond => self.conds.iter().map(|c| c.id).collect(),
+            sync::Object::Sem => self.sems.iter().map(|s| s.id).collect(),
+        }
+    }
+
+    pub(crate) fn get_mutex(&self, id: u32) -> Option<&Mutex> {
+        self.mutexes.iter().find(|m| m.id == id)
+    }
+
+    pub(crate) fn get_mutex_mut(&mut self, id: u32) -> Option<&mut Mutex> {
+        self.mutexes.iter_mut().find(|m| m.id == id)
+    }
+
+    // The wait queues of the condition variables and semaphores, which unlike those of the
+    // mutexes never pass on a priority
+    fn other_wait_queues_mut(&mut self) -> impl Iterator<Item = &mut WaitQueue> {
+        self.conds
+            .iter_mut()
+            .map(|c| &mut c.wait_queue)
+            .chain(self.sems.iter_mut().map(|s| &mut s.wait_queue))
+    }
+
+    pub(crate) fn get_sem(&self, id: u32) -> Option<&Semaphore> {
+        self.sems.iter().find(|s| s.id == id)
+    }
+
+    pub(crate) fn get_sem_mut(&mut self, id: u32) -> Option<&mut Semaphore> {
+        self.sems.iter_mut().find(|s| s.id == id)
+    }
+
+    pub(crate) fn get_cond(&self, id: u32) -> Option<&CondVar> {
+        self.conds.iter().find(|c| c.id == id)
+    }
+
+    pub(crate) fn get_cond_mut(&mut self, id: u32) -> Option<&mut CondVar> {
+        self.conds.iter_mut().find(|c| c.id == id)
+    }
+
+    // Releases a mutex the task owns. The highest-priority waiter takes it over and becomes
+    // ready and inherits from the remaining waiters, while the task drops back to the priority
+    // it has without them
+    pub(crate) fn release_mutex(&mut self, id: u32, tid: u32) {
+        let mutex = self.get_mutex_mut(id).unwrap();
+        match mutex.wait_queue.dequeue() {
+            Some(mut waiter) => {
+                let new_owner = waiter.tid;
+                mutex.owner = Some(new_owner);
+                waiter.state = sched_data::TaskState::Ready;
+                self.ready_queue.enqueue_tail(waiter);
+                self.update_priority(new_owner);
+            }
+            None => mutex.owner = None,
+        }
+        self.update_priority(tid);
+    }
+
+    // A task woken from a condition variable takes the mutex back if it is free, and otherwise
+    // waits for it as if it had called pthread_mutex_lock
+    pub(crate) fn reacquire_mutex(&mut self, id: u32, mut task: sched_data::TaskControlBlock) {
+        let tid = task.tid;
+        let mutex = self.get_mutex_mut(id).unwrap();
+        match mutex.owner {
+            Some(owner) => {
+                task.state = sched_data::TaskState::Waiting;
+                mutex.wait_queue.enqueue(task);
+                self.update_priority(owner);
+            }
+            None => {
+                mutex.owner = Some(tid);
+                task.state = sched_data::TaskState::Ready;
+                self.ready_queue.enqueue_tail(task);
+                self.update_priority(tid);
+            }
+        }
+    }
+
+    pub(crate) fn get_running_task(&self, tid: u32) -> Option<&sched_data::TaskControlBlock> {
+        self.cpu
+            .cores
+            .iter()
+            .filter_map(|core| core.task.as_ref())
+            .find(|task| task.tid == tid)
+    }
+
+    pub(crate) fn core_of(&self, tid: u32) -> Option<u32> {
+        self.cpu
+            .cores
+            .iter()
+            .find(|core| matches!(&core.task, Some(task) if task.tid == tid))
+            .map(|core| core.id)
+    }
+
+    // The first task in the ready queue that may run on the core
+    pub(crate) fn highest_ready_for(&self, core_id: u32) -> Option<&sched_data::TaskControlBlock> {
+        self.ready_queue
+            .iter()
+            .find(|task| task.can_run_on(core_id))
+    }
+
+    // The affinity that a mask given to a function stands for, None if it has no existing core
+    pub(crate) fn normalize_affinity(&self, mask: u32) -> Option<u32> {
+        match mask & self.cpu.mask() {
+            0 => None,
+            mask if mask == self.cpu.mask() => Some(sched_data::ALL_CORES),
+            mask => Some(mask),
+        }
+    }
+
+    // Restricts a live task to the cores of the affinity. A running task that may no longer
+    // stay on its core leaves it for the head of its priority level
+    pub(crate) fn set_affinity(&self, tid: u32, affinity: u32) -> Vec<State> {
+        let mut next = self.clone();
+        let core_id = self.core_of(tid);
+        let task = next.live_task_mut(tid).expect("the task is not alive");
+        task.affinity = affinity;
+
+        if let Some(core_id) = core_id {
+            if !task.can_run_on(core_id) {
+                let mut task = next.block(tid);
+                task.state = sched_data::TaskState::Ready;
+                next.ready_queue.enqueue_head(task);
+            }
+        }
+        next.schedule()
+    }
+
+    // Takes the running task off its core and puts it into the Waiting state
+    pub(crate) fn block(&mut self, tid: u32) -> sched_data::TaskControlBlock {
+        for core in self.cpu.cores.iter_mut() {
+            if matches!(&core.task, Some(task) if task.tid == tid) {
+                let mut task = core.task.take().unwrap();
+                task.state = sched_data::TaskState::Waiting;
+                return task;
+            }
+        }
+
+        unreachable!("task {} is not running", tid);
+    }
+
+    // Cores are interchangeable while no task has a restricted affinity, so states that differ
+    // only in which core runs which task are mapped to the same representative: busy cores
+    // ordered by TID, then idle cores, renumbered from 0
+    pub fn canonical(&self) -> State {
+        let mut next = self.clone();
+        if self
+            .tasks()
+            .any(|task| task.affinity != sched_data::ALL_CORES)
+        {
+            return next;
+        }
+        next.cpu
+            .cores
+            .sort_by_key(|core| core.task.as_ref().map_or(u32::MAX, |task| task.tid));
+        for (id, core) in next.cpu.cores.iter_mut().enumerate() {
+            core.id = id as u32;
+        }
+        next
+    }
+
+    // Moves the running task to the tail of its priority level in the ready queue
+    pub(crate) fn requeue(&self, tid: u32) -> State {
+        let mut next = self.clone();
+        let mut task = next.block(tid);
+        task.state = sched_data::TaskState::Ready;
+        next.ready_queue.enqueue_tail(task);
+        next
+    }
+
+    pub(crate) fn is_terminated(&self, tid: u32) -> bool {
+        self.terminated_tasks.iter().any(|task| task.tid == tid)
+    }
+
+    // Terminates the running task. Its joiner, if any, reaps it and becomes ready
+    pub(crate) fn exit(&self, tid: u32) -> State {
+        let mut next = self.clone();
+        let mut task = next.block(tid);
+        task.state = sched_data::TaskState::Terminated;
+
+        if let Some(pos) = next.joiners.iter().position(|(target, _)| *target == tid) {
+            let (_, mut joiner) = next.joiners.remove(pos);
+            joiner.state = sched_data::TaskState::Ready;
+            next.ready_queue.enqueue_tail(joiner);
+            task.detached = true;
+        }
+        next.terminated_tasks.push(task);
+        next
+    }
+
+    // The highest priority among the waiters of the priority-inheritance mutexes and the ceilings
+    // of the priority-protection mutexes that the task owns
+    fn inherited_priority(&self, tid: u32) -> Option<u32> {
+        self.mutexes
+            .iter()
+            .filter(|m| m.owner == Some(tid))
+            .filter_map(|m| match m.protocol {
+                MutexProtocol::PrioNone => None,
+                MutexProtocol::PrioInherit => m.wait_queue.iter().next().map(|task| task.prio),
+                MutexProtocol::PrioProtect => Some(m.ceiling),
+            })
+            .max()
+    }
+
+    // Brings the effective priority of a live task in line with its base priority and the
+    // priority it inherits. A boosted ready task goes to the tail of its new priority level and a
+    // deboosted one to the head, and a change of a waiter passes on to the owner of the
+    // priority-inheritance mutex, transitively through the chain of held locks
+    pub(crate) fn update_priority(&mut self, tid: u32) {
+        let inherited = self.inherited_priority(tid);
+        let (prio, old_prio) = match self.live_task_mut(tid) {
+            Some(task) => (inherited.unwrap_or(0).max(task.base_prio), task.prio),
+            None => return,
+        };
+        if prio == old_prio {
+            return;
+        }
+
+        if let Some(mut task) = self.ready_queue.remove(tid) {
+            task.prio = prio;
+            if prio > old_prio {
+                self.ready_queue.enqueue_tail(task);
+            } else {
+                self.ready_queue.enqueue_head(task);
+            }
+            return;
+        }
+        for i in 0..self.mutexes.len() {
+            if let Some(mut task) = self.mutexes[i].wait_queue.remove(tid) {
+                task.prio = prio;
+                self.mutexes[i].wait_queue.enqueue(task);
+                if let (MutexProtocol::PrioInherit, Some(owner)) =
+                    (self.mutexes[i].protocol, self.mutexes[i].owner)
+                {
+                    self.update_priority(owner);
+                }
+                return;
+            }
+        }
+        for queue in self.other_wait_queues_mut() {
+            if let Some(mut task) = queue.remove(tid) {
+                task.prio = prio;
+                queue.enqueue(task);
+                return;
+            }
+        }
+        // A running task that drops below a ready one is preempted by schedule()
+        self.live_task_mut(tid).unwrap().prio = prio;
+    }
+
+    // Gives a live task a new priority and policy. A ready task moves to the head or the tail
+    // of its new priority level, and so does a running one that loses its core by the change
+    pub(crate) fn set_priority(
+        &self,
+        tid: u32,
+        prio: u32,
+        policy: sched_data::Policy,
+        at_head: bool,
+    ) -> Vec<State> {
+        let mut next = self.clone();
+        // While the task inherits at least the new priority, that is the priority it keeps
+        if matches!(self.inherited_priority(tid), Some(inherited) if inherited >= prio) {
+            let task = next.live_task_mut(tid).expect("the task is not alive");
+            task.base_prio = prio;
+            task.policy = policy;
+            next.update_priority(tid);
+            return next.schedule();
+        }
+        let update = |task: &mut sched_data::TaskControlBlock| {
+            task.prio = prio;
+            task.base_prio = prio;
+            task.policy = policy;
+        };
+
+        if let Some(mut task) = next.ready_queue.remove(tid) {
+            update(&mut task);
+            if at_head {
+                next.ready_queue.enqueue_head(task);
+            } else {
+                next.ready_queue.enqueue_tail(task);
+            }
+            return next.schedule();
+        }
+
+        for i in 0..next.mutexes.len() {
+            if let Some(mut task) = next.mutexes[i].wait_queue.remove(tid) {
+                update(&mut task);
+                next.mutexes[i].wait_queue.enqueue(task);
+                if let (MutexProtocol::PrioInherit, Some(owner)) =
+                    (next.mutexes[i].protocol, next.mutexes[i].owner)
+                {
+                    next.update_priority(owner);
+                }
+                return next.schedule();
+            }
+        }
+        let mut waiting = false;
+        for queue in next.other_wait_queues_mut() {
+            if let Some(mut task) = queue.remove(tid) {
+                update(&mut task);
+                queue.enqueue(task);
+                waiting = true;
+                break;
+            }
+        }
+        if waiting {
+            return vec![next];
+        }
+        if let Some((_, task)) = next.joiners.iter_mut().find(|(_, task)| task.tid == tid) {
+            update(task);
+            return vec![next];
+        }
+
+        let core = next
+            .cpu
+            .cores
+            .iter_mut()
+            .find(|core| matches!(&core.task, Some(task) if task.tid == tid))
+            .expect("the task is not alive");
+        let core_id = core.id;
+        update(core.task.as_mut().unwrap());
+        // Going to the tail lets a ready task of the same priority take over the core, while
+        // a task preempted on its way to the head is requeued by schedule()
+        if !at_head && matches!(next.highest_ready_for(core_id), Some(ready) if ready.prio >= prio)
+        {
+            next = next.requeue(tid);
+        }
+        next.schedule()
+    }
+
     // Takes a task from a specified CPU and returns it to the ready queue
     pub(crate) fn interrupt(&self, cpu_id: u32) -> State {
         let mut next = self.clone();
@@ -24,8 +443,8 @@ impl State {
             if core.id == cpu_id {
                 if let Some(mut task) = next.cpu.cores[i].task.take() {
                     task.state = sched_data::TaskState::Ready;
-                    // TODO: is it ok to use enqueue here?
-                    next.ready_queue.enqueue(task);
+                    // A preempted SCHED_FIFO task runs again before the others of its priority
+                    next.ready_queue.enqueue_head(task);
                 }
                 break;
             }
@@ -35,11 +454,16 @@ impl State {
         next
     }
 
-    // Dispatches a task to an idle random CPU core.
+    // Dispatches a task to a random idle CPU core that its affinity allows.
     pub(crate) fn dispatch(&self, task: sched_data::TaskControlBlock) -> Vec<State> {
         let mut nexts = vec![];
 
-        for idle_core in self.cpu.get_idle_cores().iter() {
+        for idle_core in self
+            .cpu
+            .get_idle_cores()
+            .iter()
+            .filter(|core| task.can_run_on(core.id))
+        {
             let mut next = self.clone();
             for (i, core) in self.cpu.cores.iter().enumerate() {
                 if core.id == idle_core.id {
@@ -54,27 +478,49 @@ impl State {
         nexts
     }
 
-    // Create a new task and enqueue it to the ready queue
-    pub(crate) fn create_task(&self, prio: u32) -> State {
-        let tid = {
-            let mut tid = 0;
+    // The distinct priorities a new priority may be compared to, i.e. those of the live tasks and
+    // the ceilings of the PTHREAD_PRIO_PROTECT mutexes, in ascending order
+    pub(crate) fn priorities(&self) -> Vec<u32> {
+        self.tasks()
+            .filter(|task| task.state != sched_data::TaskState::Terminated)
+            .flat_map(|task| [task.prio, task.base_prio])
+            .chain(
+                self.mutexes
+                    .iter()
+                    .filter(|m| m.protocol == MutexProtocol::PrioProtect)
+                    .map(|m| m.ceiling),
+            )
+            .sorted()
+            .dedup()
+            .collect()
+    }
 
-            for core in self.cpu.cores.iter() {
-                if let Some(task) = &core.task {
-                    tid = std::cmp::max(tid, task.tid);
-                }
-            }
-            for tcb in self.ready_queue.iter() {
-                tid = std::cmp::max(tid, tcb.tid);
-            }
+    // TIDs are never reused, as in the test harness
+    pub(crate) fn next_tid(&self) -> u32 {
+        self.tasks().map(|task| task.tid).max().unwrap_or(0) + 1
+    }
 
-            tid + 1
-        };
+    // Create a new task and enqueue it to the ready queue
+    #[cfg(test)]
+    pub(crate) fn create_task(&self, prio: u32) -> State {
+        self.create_task_with_attr(prio, sched_data::Policy::Fifo, false, sched_data::ALL_CORES)
+    }
 
-        let mut new_task = sched_data::TaskControlBlock::new(tid, prio);
+    // Creates a task with the attributes given to pthread_create
+    pub(crate) fn create_task_with_attr(
+        &self,
+        prio: u32,
+        policy: sched_data::Policy,
+        detached: bool,
+        affinity: u32,
+    ) -> State {
+        let mut new_task = sched_data::TaskControlBlock::new(self.next_tid(), prio);
+        new_task.policy = policy;
+        new_task.detached = detached;
+        new_task.affinity = affinity;
         new_task.state = sched_data::TaskState::Ready;
         let mut next = self.clone();
-        next.ready_queue.enqueue(new_task);
+        next.ready_queue.enqueue_tail(new_task);
         next
     }
 
@@ -83,23 +529,27 @@ impl State {
         let mut made_progress = false;
 
         while {
-            let mut new_states = vec![];
-            for state in states.into_iter() {
-                if !state.cpu.get_idle_cores().is_empty() && state.ready_queue.front().is_some() {
-                    made_progress = true;
-                    let mut current_state = state;
-                    if let Some(task) = current_state.ready_queue.dequeue() {
-                        for st in current_state.dispatch(task).into_iter() {
-                            if !new_states.contains(&st) {
-                                new_states.push(st);
-                            }
-                        }
+            states = states
+                .into_iter()
+                .flat_map(|mut state| {
+                    // The first ready task that may run on one of the idle cores
+                    let idle_cores: Vec<u32> = state
+                        .cpu
+                        .get_idle_cores()
+                        .iter()
+                        .map(|core| core.id)
+                        .collect();
+                    let ready = state
+                        .ready_queue
+                        .dequeue_where(|task| idle_cores.iter().any(|&id| task.can_run_on(id)));
+                    if let Some(task) = ready {
+                        made_progress = true;
+                        return state.dispatch(task);
                     }
-                } else if !new_states.contains(&state) {
-                    new_states.push(state);
-                }
-            }
-            states = new_states;
+                    vec![state]
+                })
+                .unique()
+                .collect();
             made_progress
         } {
             made_progress = false;
@@ -111,12 +561,10 @@ impl State {
 
     pub(crate) fn preempt_to_lower_priority_tasks(&self) -> Vec<State> {
         let mut new_states = vec![];
-        if let Some(front_task) = self.ready_queue.front() {
-            for core in self.cpu.cores.iter() {
-                if let Some(task) = &core.task {
-                    if front_task.prio > task.prio {
-                        new_states.push(self.interrupt(core.id));
-                    }
+        for core in self.cpu.cores.iter() {
+            if let (Some(task), Some(ready)) = (&core.task, self.highest_ready_for(core.id)) {
+                if ready.prio > task.prio {
+                    new_states.push(self.interrupt(core.id));
                 }
             }
         }
@@ -130,27 +578,19 @@ impl State {
 
     pub(crate) fn schedule(&self) -> Vec<State> {
         let mut prev_states = vec![self.clone()];
-        let mut new_states = vec![];
+        let mut new_states;
 
         while {
-            for prev_state in prev_states.iter() {
-                for dispatched_state in prev_state.dispatch_to_all_idle_cores().into_iter() {
-                    for preempted_state in dispatched_state
-                        .preempt_to_lower_priority_tasks()
-                        .into_iter()
-                    {
-                        for new_state in preempted_state.dispatch_to_all_idle_cores().into_iter() {
-                            if !new_states.contains(&new_state) {
-                                new_states.push(new_state);
-                            }
-                        }
-                    }
-                }
-            }
+            new_states = prev_states
+                .iter()
+                .flat_map(|prev_state| prev_state.dispatch_to_all_idle_cores())
+                .flat_map(|dispatched_state| dispatched_state.preempt_to_lower_priority_tasks())
+                .flat_map(|preempted_state| preempted_state.dispatch_to_all_idle_cores())
+                .unique()
+                .collect();
             new_states != prev_states
         } {
             prev_states = new_states;
-            new_states = vec![];
         }
 
         assert!(!new_states.is_empty());
@@ -160,68 +600,116 @@ impl State {
 
 #[cfg(test)]
 mod test {
-    use crate::spec::{
-        cpu::{Core, CPU},
-        sched_data::{ReadyQueue, TaskControlBlock, TaskState},
-        scheduler::State,
-    };
-    use std::collections::VecDeque;
+    use crate::spec::{sched_data, scheduler::State};
 
     #[test]
     fn test_preempt_to_lower_priority_tasks() {
-        let init = State {
-            cpu: CPU {
-                cores: vec![Core {
-                    id: 0,
-                    task: Some(TaskControlBlock {
-                        tid: 1,
-                        prio: 3,
-                        state: TaskState::Running,
-                    }),
-                }],
-            },
-            ready_queue: ReadyQueue(VecDeque::from(vec![
-                TaskControlBlock {
-                    tid: 3,
-                    prio: 4,
-                    state: TaskState::Ready,
-                },
-                TaskControlBlock {
-                    tid: 2,
-                    prio: 1,
-                    state: TaskState::Ready,
-                },
-            ])),
-            terminated_tasks: vec![],
-        };
+        let init: State = "C0:T1/p3 RQ:[T3/p4,T2/p1] X:[] M0:-".parse().unwrap();
 
         let states = init.preempt_to_lower_priority_tasks();
 
         println!("{:?}", states);
-        let expected_result = vec![State {
-            cpu: CPU {
-                cores: vec![Core { id: 0, task: None }],
-            },
-            ready_queue: ReadyQueue(VecDeque::from(vec![
-                TaskControlBlock {
-                    tid: 3,
-                    prio: 4,
-                    state: TaskState::Ready,
-                },
-                TaskControlBlock {
-                    tid: 1,
-                    prio: 3,
-                    state: TaskState::Ready,
-                },
-                TaskControlBlock {
-                    tid: 2,
-                    prio: 1,
-                    state: TaskState::Ready,
-                },
-            ])),
-            terminated_tasks: vec![],
-        }];
+        let expected_result: Vec<State> =
+            vec!["C0:- RQ:[T3/p4,T1/p3,T2/p1] X:[] M0:-".parse().unwrap()];
 
         assert_eq!(expected_result, states);
     }
+    #[test]
+    fn test_requeue_position() {
+        // A preempted task goes to the head of its priority level
+        let state: State = "C0:T1/p2 RQ:[T3/p3,T2/p2] X:[] M0:-".parse().unwrap();
+        assert_eq!(
+            state.preempt_to_lower_priority_tasks()[0].to_string(),
+            "C0:- RQ:[T3/p3,T1/p2,T2/p2] X:[] M0:-"
+        );
+        assert_eq!(
+            state.schedule()[0].to_string(),
+            "C0:T3/p3 RQ:[T1/p2,T2/p2] X:[] M0:-"
+        );
+
+        // A new task or one whose time slice expired goes to the tail
+        let state: State = "C0:T1/p2/rr RQ:[T2/p2] X:[] M0:-".parse().unwrap();
+        assert_eq!(
+            state.create_task(2).to_string(),
+            "C0:T1/p2/rr RQ:[T2/p2,T3/p2] X:[] M0:-"
+        );
+        assert_eq!(
+            state.requeue(1).to_string(),
+            "C0:- RQ:[T2/p2,T1/p2/rr] X:[] M0:-"
+        );
+    }
+
+    #[test]
+    fn test_canonical() {
+        let states = State::new(3).create_task(1).create_task(1).schedule();
+        assert_eq!(states.len(), 6);
+
+        let canonical = states[0].canonical();
+        for state in states.iter() {
+            assert_eq!(state.canonical(), canonical);
+        }
+        assert_eq!(
+            canonical
+                .cpu
+                .cores
+                .iter()
+                .map(|core| (core.id, core.task.as_ref().map(|t| t.tid)))
+                .collect::<Vec<_>>(),
+            vec![(0, Some(1)), (1, Some(2)), (2, None)]
+        );
+        assert_eq!(canonical.canonical(), canonical);
+
+        // Cores are no longer interchangeable once a task is restricted to some of them
+        let state: State = "C0:- C1:T2/p1/a2 C2:T1/p1 RQ:[] X:[] M0:-".parse().unwrap();
+        assert_eq!(state.canonical(), state);
+    }
+
+    #[test]
+    fn test_update_priority() {
+        // A boosted task goes to the tail of its new priority level and a deboosted one to the head
+        let mut state: State = "C0:T1/p3 RQ:[T4/p2,T2/p1/b1,T3/p1] X:[] M0i:T2[T5/p2]"
+            .parse()
+            .unwrap();
+        state.update_priority(2);
+        assert_eq!(
+            state.to_string(),
+            "C0:T1/p3 RQ:[T4/p2,T2/p2/b1,T3/p1] X:[] M0i:T2[T5/p2]"
+        );
+        state.mutexes[0].wait_queue.0.clear();
+        state.update_priority(2);
+        assert_eq!(
+            state.to_string(),
+            "C0:T1/p3 RQ:[T4/p2,T2/p1,T3/p1] X:[] M0i:T2"
+        );
+
+        // A new priority of a waiter passes on to the owner
+        let state: State = "C0:T1/p3 RQ:[T2/p2/b1] X:[] M0i:T2[T3/p2]".parse().unwrap();
+        assert_eq!(
+            state
+                .set_priority(3, 4, sched_data::Policy::Fifo, false)
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>(),
+            vec!["C0:T2/p4/b1 RQ:[T1/p3] X:[] M0i:T2[T3/p4]"]
+        );
+    }
+
+    #[test]
+    fn test_affinity() {
+        // A task is only dispatched to and preempts the cores it may run on
+        let init: State = "C0:- C1:T1/p1 RQ:[T2/p2/a2,T3/p1] X:[] M0:-"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            init.schedule()
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>(),
+            vec!["C0:T3/p1 C1:T2/p2/a2 RQ:[T1/p1] X:[] M0:-"]
+        );
+        assert_eq!(init.highest_ready_for(0).map(|t| t.tid), Some(3));
+        assert_eq!(init.normalize_affinity(4), None);
+        assert_eq!(init.normalize_affinity(5), Some(1));
+        assert_eq!(init.normalize_affinity(7), Some(sched_data::ALL_CORES));
+    }
 }
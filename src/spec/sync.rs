@@ -0,0 +1,110 @@
+use strum_macros::{Display, EnumString};
+
+use crate::spec::sched_data::WaitQueue;
+
+// The test harness initializes a single global mutex
+pub(crate) const NUM_MUTEXES: u32 = 1;
+
+// The test harness initializes a single global condition variable
+pub(crate) const NUM_CONDS: u32 = 1;
+
+// The test harness initializes a single global semaphore with the value 0
+pub(crate) const NUM_SEMS: u32 = 1;
+
+// The kinds of synchronization objects, each numbered from 0
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Object {
+    Mutex,
+    Cond,
+    Sem,
+}
+
+// PTHREAD_MUTEX_NORMAL and PTHREAD_MUTEX_ERRORCHECK
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum MutexKind {
+    Normal,
+    ErrorCheck,
+}
+
+// The priority ceiling of a mutex unless it is given, sched_get_priority_max(SCHED_FIFO)
+pub(crate) const DEFAULT_CEILING: u32 = 99;
+
+// PTHREAD_PRIO_NONE, PTHREAD_PRIO_INHERIT and PTHREAD_PRIO_PROTECT, named as in the options and
+// scenarios
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, EnumString)]
+pub enum MutexProtocol {
+    #[strum(serialize = "none")]
+    PrioNone,
+    #[strum(serialize = "inherit")]
+    PrioInherit,
+    #[strum(serialize = "protect")]
+    PrioProtect,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct Mutex {
+    pub(crate) id: u32,
+    pub(crate) kind: MutexKind,
+    pub(crate) protocol: MutexProtocol,
+    // The priority its owner runs at least at under PTHREAD_PRIO_PROTECT
+    pub(crate) ceiling: u32,
+    pub(crate) owner: Option<u32>,
+    pub(crate) wait_queue: WaitQueue,
+}
+
+impl Mutex {
+    pub(crate) const fn new(id: u32) -> Self {
+        Mutex::with_kind(id, MutexKind::ErrorCheck)
+    }
+
+    pub(crate) const fn with_kind(id: u32, kind: MutexKind) -> Self {
+        Mutex {
+            id,
+            kind,
+            protocol: MutexProtocol::PrioNone,
+            ceiling: DEFAULT_CEILING,
+            owner: None,
+            wait_queue: WaitQueue::new(),
+        }
+    }
+
+    // A thread above the ceiling of a PTHREAD_PRIO_PROTECT mutex may not lock it
+    pub(crate) fn violates_ceiling(&self, prio: u32) -> bool {
+        self.protocol == MutexProtocol::PrioProtect && prio > self.ceiling
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct CondVar {
+    pub(crate) id: u32,
+    // The mutex the waiters released and take back when they are woken, bound while they wait
+    pub(crate) mutex: Option<u32>,
+    pub(crate) wait_queue: WaitQueue,
+}
+
+impl CondVar {
+    pub(crate) const fn new(id: u32) -> Self {
+        CondVar {
+            id,
+            mutex: None,
+            wait_queue: WaitQueue::new(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct Semaphore {
+    pub(crate) id: u32,
+    pub(crate) count: u32,
+    pub(crate) wait_queue: WaitQueue,
+}
+
+impl Semaphore {
+    pub(crate) const fn new(id: u32) -> Self {
+        Semaphore {
+            id,
+            count: 0,
+            wait_queue: WaitQueue::new(),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use crate::oracle_tree::Step;
+use crate::spec::{
+    function::{get_function, Function, Transition},
+    scheduler,
+};
+
+// Unlike the oracle tree, a state reached by different call sequences is stored only once
+#[derive(Debug, PartialEq, Eq)]
+pub struct StateGraph {
+    states: Vec<scheduler::State>,
+    // State |-> its ID
+    ids: HashMap<scheduler::State, usize>,
+    edges: Vec<Vec<GraphEdge>>,
+    // The edge (source ID, edge index) through which each state was first reached
+    parents: Vec<Option<(usize, usize)>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub fn_type: Function,
+    pub caller: u32,
+    pub args: Vec<u32>,
+    pub ret: usize,
+    pub targets: Vec<usize>,
+}
+
+impl StateGraph {
+    pub const ROOT: usize = 0;
+
+    // A graph holding the initial state and the states right after the launch
+    pub fn init(num_core: u32) -> Self {
+        let initial_state = scheduler::State::new(num_core);
+        let transitions = get_function(Function::Spawn).call(&initial_state, 0, &[]);
+        StateGraph::from_spawn(initial_state, transitions)
+    }
+
+    pub fn from_spawn(initial_state: scheduler::State, transitions: Vec<Transition>) -> Self {
+        let mut graph = StateGraph {
+            states: vec![],
+            ids: HashMap::new(),
+            edges: vec![],
+            parents: vec![],
+        };
+        graph.insert(initial_state, None);
+        graph.add_edges(StateGraph::ROOT, Function::Spawn, 0, vec![], transitions);
+        graph
+    }
+
+    pub fn get_id(&self, state: &scheduler::State) -> Option<usize> {
+        self.ids.get(state).copied()
+    }
+
+    // Returns the ID of the state and whether it has been newly inserted
+    fn insert(&mut self, state: scheduler::State, parent: Option<(usize, usize)>) -> (usize, bool) {
+        if let Some(id) = self.get_id(&state) {
+            return (id, false);
+        }
+
+        let id = self.states.len();
+        self.ids.insert(state.clone(), id);
+        self.states.push(state);
+        self.edges.push(vec![]);
+        self.parents.push(parent);
+        (id, true)
+    }
+
+    // Adds one edge per distinct return value and returns the IDs of the newly found states
+    pub fn add_edges(
+        &mut self,
+        from: usize,
+        fn_type: Function,
+        caller: u32,
+        args: Vec<u32>,
+        transitions: Vec<Transition>,
+    ) -> Vec<usize> {
+        let mut new_ids = vec![];
+        for Transition { ret, next } in transitions.into_iter() {
+            let edge_idx = match self.edges[from].iter().position(|edge| {
+                edge.fn_type == fn_type
+                    && edge.caller == caller
+                    && edge.args == args
+                    && edge.ret == ret
+            }) {
+                Some(idx) => idx,
+                None => {
+                    self.edges[from].push(GraphEdge {
+                        fn_type,
+                        caller,
+                        args: args.clone(),
+                        ret,
+                        targets: vec![],
+                    });
+                    self.edges[from].len() - 1
+                }
+            };
+
+            let (id, is_new) = self.insert(next, Some((from, edge_idx)));
+            if is_new {
+                new_ids.push(id);
+            }
+            let targets = &mut self.edges[from][edge_idx].targets;
+            if !targets.contains(&id) {
+                targets.push(id);
+            }
+        }
+        new_ids
+    }
+
+    pub fn get_state(&self, id: usize) -> &scheduler::State {
+        &self.states[id]
+    }
+
+    pub fn get_edges(&self, id: usize) -> &[GraphEdge] {
+        &self.edges[id]
+    }
+
+    pub fn num_states(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.edges.iter().map(|edges| edges.len()).sum()
+    }
+
+    // The steps of the shortest call sequence from the root to the state
+    pub fn path_to(&self, id: usize) -> Vec<Step> {
+        let mut path = vec![];
+        let mut current = id;
+        while let Some((from, edge_idx)) = self.parents[current] {
+            let edge = &self.edges[from][edge_idx];
+            path.push(Step {
+                fn_type: edge.fn_type,
+                caller: edge.caller,
+                args: edge.args.clone(),
+                ret: edge.ret,
+                state: self.states[current].clone(),
+                group: edge
+                    .targets
+                    .iter()
+                    .map(|&target| self.states[target].clone())
+                    .collect(),
+            });
+            current = from;
+        }
+        path.reverse();
+        path
+    }
+
+    // One path per state, which covers every state of the graph
+    pub fn paths(&self) -> Vec<Vec<Step>> {
+        (0..self.states.len())
+            .filter(|&id| id != StateGraph::ROOT)
+            .map(|id| self.path_to(id))
+            .collect()
+    }
+
+    // Exports the graph in the Graphviz DOT format
+    pub fn to_dot(&self) -> String {
+        let mut lines = vec!["digraph state_graph {".to_string()];
+        for (id, state) in self.states.iter().enumerate() {
+            lines.push(format!("  n{} [label=\"{:?}\"];", id, state));
+        }
+        for (id, edges) in self.edges.iter().enumerate() {
+            for edge in edges.iter() {
+                for target in edge.targets.iter() {
+                    lines.push(format!(
+                        "  n{} -> n{} [label=\"{}: {:?}{:?} = {}\"];",
+                        id, target, edge.caller, edge.fn_type, edge.args, edge.ret
+                    ));
+                }
+            }
+        }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StateGraph;
+    use crate::spec::function::{get_function, Function};
+
+    #[test]
+    fn test_state_graph_dedup() {
+        let mut graph = StateGraph::init(2);
+        assert_eq!(graph.num_states(), 3);
+        assert_eq!(graph.get_edges(StateGraph::ROOT)[0].targets, vec![1, 2]);
+
+        // Exiting task 1 from either core leads to the same state
+        let exit = get_function(Function::PthreadExit);
+        let new_ids = graph.add_edges(
+            1,
+            Function::PthreadExit,
+            1,
+            vec![],
+            exit.call(graph.get_state(1), 1, &[]),
+        );
+        assert_eq!(new_ids, vec![3]);
+        let new_ids = graph.add_edges(
+            2,
+            Function::PthreadExit,
+            1,
+            vec![],
+            exit.call(graph.get_state(2), 1, &[]),
+        );
+        assert!(new_ids.is_empty());
+        assert_eq!(graph.num_states(), 4);
+        assert_eq!(graph.get_edges(2)[0].targets, vec![3]);
+
+        // The first path found is kept
+        let path = graph.path_to(3);
+        assert_eq!(path.len(), 2);
+        assert_eq!(&path[0].state, graph.get_state(1));
+        assert_eq!(graph.get_id(graph.get_state(3)), Some(3));
+    }
+}